@@ -0,0 +1,94 @@
+use crate::AppState;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+
+// Once the log grows past this size it is moved to `{path}.1`, replacing the
+// previous rotation
+const AUDIT_LOG_MAX_BYTES: u64 = 1024 * 1024;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub timestamp: u64,
+    pub tool: String,
+    // Env and API key values are redacted by the caller before logging
+    pub arguments: serde_json::Value,
+    pub result: serde_json::Value,
+}
+
+// Append one JSON line for an MCP tool call. Does nothing unless an audit log path is
+// configured; write failures are reported but never fail the tool call itself.
+pub async fn record(
+    state: &AppState,
+    tool: &str,
+    arguments: serde_json::Value,
+    result: &serde_json::Value,
+) {
+    let path = match state.settings_cache.read().await.audit_log_path() {
+        Some(path) => path.to_string(),
+        None => return,
+    };
+
+    let entry = AuditEntry {
+        timestamp: crate::unix_now(),
+        tool: tool.to_string(),
+        arguments,
+        result: result.clone(),
+    };
+
+    if let Err(e) = append_entry(&path, &entry) {
+        tracing::warn!(error = %e, "Failed to write audit log");
+    }
+}
+
+fn append_entry(path: &str, entry: &AuditEntry) -> Result<(), String> {
+    if let Some(parent) = Path::new(path).parent() {
+        if !parent.as_os_str().is_empty() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create audit log directory: {}", e))?;
+        }
+    }
+
+    let needs_rotation = fs::metadata(path)
+        .map(|metadata| metadata.len() >= AUDIT_LOG_MAX_BYTES)
+        .unwrap_or(false);
+    if needs_rotation {
+        fs::rename(path, rotated_path(path))
+            .map_err(|e| format!("Failed to rotate audit log: {}", e))?;
+    }
+
+    let line = serde_json::to_string(entry)
+        .map_err(|e| format!("Failed to serialize audit entry: {}", e))?;
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| format!("Failed to open audit log: {}", e))?;
+    writeln!(file, "{}", line).map_err(|e| format!("Failed to append to audit log: {}", e))
+}
+
+fn rotated_path(path: &str) -> String {
+    format!("{}.1", path)
+}
+
+// The last `limit` entries, oldest first, reaching into the rotated file when needed.
+// Lines that don't parse (e.g. a partially written last line) are skipped.
+pub fn read_tail(path: &str, limit: usize) -> Result<Vec<AuditEntry>, String> {
+    let mut entries = Vec::new();
+
+    for file in [rotated_path(path), path.to_string()] {
+        match fs::read_to_string(&file) {
+            Ok(content) => entries.extend(
+                content
+                    .lines()
+                    .filter_map(|line| serde_json::from_str::<AuditEntry>(line).ok()),
+            ),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(format!("Failed to read audit log: {}", e)),
+        }
+    }
+
+    let skip = entries.len().saturating_sub(limit);
+    Ok(entries.into_iter().skip(skip).collect())
+}
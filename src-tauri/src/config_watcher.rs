@@ -0,0 +1,135 @@
+use crate::AppState;
+use notify::{EventKind, RecursiveMode, Watcher};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+
+// Wait this long after the last change before reloading, so editors that
+// write in several steps only trigger a single reload
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+// Start (or re-arm) the watcher for the given config path, replacing any previous one
+pub async fn start_config_watcher(
+    app_handle: tauri::AppHandle,
+    state: AppState,
+    config_path: String,
+) -> Result<(), String> {
+    stop_config_watcher(&state).await;
+
+    let path = PathBuf::from(&config_path);
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| format!("Invalid config path: {}", config_path))?
+        .to_os_string();
+    // Watch the parent directory since many editors replace the file on save
+    let watch_dir = path
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or_else(|| Path::new("."))
+        .to_path_buf();
+
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+    let mut watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+        let _ = tx.send(res);
+    })
+    .map_err(|e| format!("Failed to create config watcher: {}", e))?;
+
+    watcher
+        .watch(&watch_dir, RecursiveMode::NonRecursive)
+        .map_err(|e| format!("Failed to watch {}: {}", watch_dir.display(), e))?;
+
+    // Remember the current content so the first external edit can be described
+    if let Ok((config, _)) = state.load_config(Some(config_path.clone())).await {
+        state
+            .config_cache
+            .write()
+            .await
+            .insert(config_path.clone(), config);
+    }
+
+    let cancellation_token = CancellationToken::new();
+    {
+        let mut token_guard = state.config_watcher_cancellation.write().await;
+        *token_guard = Some(cancellation_token.clone());
+    }
+
+    tokio::spawn(async move {
+        // Keep the watcher alive for as long as this task runs
+        let _watcher = watcher;
+
+        loop {
+            tokio::select! {
+                _ = cancellation_token.cancelled() => break,
+                event = rx.recv() => {
+                    match event {
+                        None => break,
+                        Some(Ok(event)) if is_relevant_event(&event, &file_name) => {}
+                        Some(Ok(_)) => continue,
+                        Some(Err(e)) => {
+                            tracing::warn!(error = %e, "Config watcher error");
+                            continue;
+                        }
+                    }
+
+                    // Debounce rapid successive writes
+                    while let Ok(Some(_)) = tokio::time::timeout(DEBOUNCE, rx.recv()).await {}
+
+                    reload_config(&app_handle, &state, &config_path).await;
+                }
+            }
+        }
+    });
+
+    Ok(())
+}
+
+pub async fn stop_config_watcher(state: &AppState) {
+    let token = {
+        let mut token_guard = state.config_watcher_cancellation.write().await;
+        token_guard.take()
+    };
+
+    if let Some(token) = token {
+        token.cancel();
+    }
+}
+
+fn is_relevant_event(event: &notify::Event, file_name: &std::ffi::OsStr) -> bool {
+    matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_))
+        && event
+            .paths
+            .iter()
+            .any(|path| path.file_name() == Some(file_name))
+}
+
+async fn reload_config(app_handle: &tauri::AppHandle, state: &AppState, config_path: &str) {
+    let previous = state.config_cache.read().await.get(config_path).cloned();
+
+    match state.load_config(Some(config_path.to_string())).await {
+        Ok((config, _)) => {
+            // Our own saves update the cache first, so an unchanged config means nothing external happened
+            let previous_value = previous
+                .as_ref()
+                .and_then(|previous| serde_json::to_value(previous).ok());
+            if serde_json::to_value(&config).ok() == previous_value {
+                return;
+            }
+            state
+                .config_cache
+                .write()
+                .await
+                .insert(config_path.to_string(), config.clone());
+
+            let mut payload = previous
+                .map(|previous| crate::ConfigChange::between(&previous, &config).payload())
+                .unwrap_or_else(|| serde_json::json!({}));
+            payload["source"] = serde_json::json!("external");
+            state
+                .emit_event(app_handle, "config-changed", payload)
+                .await;
+        }
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to reload config after external change");
+        }
+    }
+}
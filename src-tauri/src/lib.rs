@@ -1,3 +1,4 @@
+use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::env;
@@ -9,20 +10,61 @@ use tauri::Manager as _;
 use tokio::sync::RwLock;
 use tokio_util::sync::CancellationToken;
 
+pub mod audit_log;
+pub mod config_watcher;
 pub mod mcp_server;
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct McpServer {
     command: String,
+    #[serde(default)]
     args: Vec<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_sorted_env"
+    )]
     env: Option<HashMap<String, String>>,
+    // Working directory Claude Desktop launches the server in
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    cwd: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+// Write env vars sorted by key so saves produce stable diffs
+fn serialize_sorted_env<S: serde::Serializer>(
+    env: &Option<HashMap<String, String>>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    env.as_ref()
+        .map(|env| env.iter().collect::<std::collections::BTreeMap<_, _>>())
+        .serialize(serializer)
+}
+
+// Server maps keep the order they were read in, so saving doesn't shuffle the file
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct ClaudeConfig {
     #[serde(rename = "mcpServers")]
-    pub mcp_servers: HashMap<String, McpServer>,
+    pub mcp_servers: IndexMap<String, McpServer>,
+    // Servers switched off in MCP Manager; Claude Desktop ignores unknown top-level keys
+    #[serde(
+        rename = "_disabledServers",
+        default,
+        skip_serializing_if = "IndexMap::is_empty"
+    )]
+    pub disabled_servers: IndexMap<String, McpServer>,
+}
+
+impl ClaudeConfig {
+    // A server from either map, with whether it is enabled
+    fn find_server(&self, name: &str) -> Option<(bool, &McpServer)> {
+        self.mcp_servers
+            .get(name)
+            .map(|server| (true, server))
+            .or_else(|| {
+                self.disabled_servers
+                    .get(name)
+                    .map(|server| (false, server))
+            })
+    }
 }
 
 #[derive(Debug, Serialize)]
@@ -31,19 +73,36 @@ struct McpServerInfo {
     command: String,
     args: Vec<String>,
     env: HashMap<String, String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cwd: Option<String>,
+    enabled: bool,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 struct McpServerEdit {
     command: String,
     args: Vec<String>,
     env: HashMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    cwd: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
 struct SaveResult {
     success: bool,
     message: String,
+    // The config file a mutating command wrote, so callers can tell which one it was
+    #[serde(skip_serializing_if = "Option::is_none")]
+    path: Option<String>,
+}
+
+// Outcome of a simulated add/update/delete that was not written to disk
+#[derive(Debug)]
+struct DryRunResult {
+    success: bool,
+    message: String,
+    before: Option<McpServer>,
+    after: Option<McpServer>,
 }
 
 #[derive(Debug, Serialize)]
@@ -54,6 +113,139 @@ struct JsonErrorInfo {
     column: Option<usize>,
     suggestion: Option<String>,
     has_backup: bool,
+    // The offending line with one line of context either side, plus a caret under the column
+    context_lines: Vec<String>,
+}
+
+// Filesystem failure while reading a config, sent to the frontend as `IO_ERROR:{json}`
+#[derive(Debug, Serialize)]
+#[serde(tag = "kind")]
+enum ConfigIoError {
+    NotFound { path: String, message: String },
+    PermissionDenied { path: String, message: String },
+    Other { path: String, message: String },
+}
+
+impl ConfigIoError {
+    fn from_io_error(path: &str, error: &std::io::Error) -> Self {
+        let path = path.to_string();
+        match error.kind() {
+            std::io::ErrorKind::NotFound => ConfigIoError::NotFound {
+                path,
+                message: "The configuration file does not exist".to_string(),
+            },
+            std::io::ErrorKind::PermissionDenied => ConfigIoError::PermissionDenied {
+                path,
+                message: "Permission denied while reading the configuration file".to_string(),
+            },
+            _ => ConfigIoError::Other {
+                path,
+                message: format!("Failed to read Claude Desktop config: {}", error),
+            },
+        }
+    }
+
+    fn to_error_string(&self) -> String {
+        let error_json = serde_json::to_string(self).unwrap_or_else(|_| {
+            "{\"kind\":\"Other\",\"path\":\"\",\"message\":\"Failed to read config\"}".to_string()
+        });
+        format!("IO_ERROR:{}", error_json)
+    }
+}
+
+// Error returned by the core commands, serialized as `{ "kind": "...", "message": ..., ... }`
+// so the frontend can branch on `kind` instead of matching message text
+#[derive(Debug, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum AppError {
+    Io {
+        path: Option<String>,
+        message: String,
+    },
+    // `details` carries the JsonErrorInfo when the failure came from analyze_json_error
+    Parse {
+        message: String,
+        details: Option<serde_json::Value>,
+    },
+    Validation {
+        message: String,
+    },
+    NotFound {
+        path: Option<String>,
+        message: String,
+    },
+    PortInUse {
+        port: u16,
+        message: String,
+    },
+    Other {
+        message: String,
+    },
+}
+
+impl AppError {
+    pub fn message(&self) -> &str {
+        match self {
+            AppError::Io { message, .. }
+            | AppError::Parse { message, .. }
+            | AppError::Validation { message }
+            | AppError::NotFound { message, .. }
+            | AppError::PortInUse { message, .. }
+            | AppError::Other { message } => message,
+        }
+    }
+}
+
+impl std::fmt::Display for AppError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.message())
+    }
+}
+
+// Classify the string errors the internal helpers produce, unpacking the `JSON_ERROR:` and
+// `IO_ERROR:` payloads so no detail is lost
+impl From<String> for AppError {
+    fn from(error: String) -> Self {
+        let payload_field = |payload: &serde_json::Value, field: &str| {
+            payload
+                .get(field)
+                .and_then(serde_json::Value::as_str)
+                .map(str::to_string)
+        };
+
+        if let Some(payload) = error.strip_prefix("JSON_ERROR:") {
+            let details = serde_json::from_str::<serde_json::Value>(payload).ok();
+            let message = details
+                .as_ref()
+                .and_then(|details| payload_field(details, "message"))
+                .unwrap_or_else(|| "Failed to parse configuration".to_string());
+            return AppError::Parse { message, details };
+        }
+
+        if let Some(payload) = error.strip_prefix("IO_ERROR:") {
+            let payload = serde_json::from_str::<serde_json::Value>(payload).unwrap_or_default();
+            let path = payload_field(&payload, "path");
+            let message = payload_field(&payload, "message")
+                .unwrap_or_else(|| "Failed to read configuration".to_string());
+            return match payload_field(&payload, "kind").as_deref() {
+                Some("NotFound") => AppError::NotFound { path, message },
+                _ => AppError::Io { path, message },
+            };
+        }
+
+        if error.starts_with("Configuration validation failed") {
+            return AppError::Validation { message: error };
+        }
+
+        AppError::Other { message: error }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct SchemaValidationError {
+    // JSON pointer to the offending value, e.g. `/mcpServers/time/command`
+    path: String,
+    message: String,
 }
 
 #[derive(Debug, Serialize)]
@@ -64,6 +256,340 @@ struct BackupInfo {
     is_valid: bool,
 }
 
+// A backup or leftover file next to the config
+#[derive(Debug, Serialize)]
+struct ConfigArtifact {
+    path: String,
+    // "backup", "timestamped_backup", "manual_backup" or "broken"
+    kind: String,
+    size: u64,
+    // Unix seconds
+    modified: u64,
+}
+
+#[derive(Debug, Serialize)]
+struct ArtifactCleanupResult {
+    deleted: Vec<String>,
+    failed: Vec<String>,
+    message: String,
+}
+
+#[derive(Debug, Serialize)]
+struct PlatformPaths {
+    os: String,
+    arch: String,
+    claude_config_path: Option<String>,
+    claude_config_error: Option<String>,
+    claude_config_exists: bool,
+    settings_path: Option<String>,
+    settings_error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct ProfileInfo {
+    name: String,
+    server_count: usize,
+    // Unix seconds
+    modified: u64,
+}
+
+// How the MCP server talks to clients: over HTTP with SSE, or on the app's own
+// stdin/stdout when a client launches MCP Manager as a child process
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum McpTransport {
+    #[default]
+    Sse,
+    Stdio,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+enum ConflictStrategy {
+    Skip,
+    Overwrite,
+    Rename,
+}
+
+// Interchange formats for export_config_as/import_config_from; the file on disk stays JSON
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+enum ConfigFormat {
+    Json,
+    Yaml,
+    Toml,
+}
+
+impl ConfigFormat {
+    fn serialize(self, config: &ClaudeConfig) -> Result<String, String> {
+        match self {
+            ConfigFormat::Json => serde_json::to_string_pretty(config).map_err(|e| e.to_string()),
+            ConfigFormat::Yaml => serde_yaml::to_string(config).map_err(|e| e.to_string()),
+            ConfigFormat::Toml => toml::to_string_pretty(config).map_err(|e| e.to_string()),
+        }
+        .map_err(|e| format!("Failed to serialize config: {}", e))
+    }
+
+    fn deserialize(self, content: &str) -> Result<ClaudeConfig, String> {
+        match self {
+            ConfigFormat::Json => serde_json::from_str(content).map_err(|e| e.to_string()),
+            ConfigFormat::Yaml => serde_yaml::from_str(content).map_err(|e| e.to_string()),
+            ConfigFormat::Toml => toml::from_str(content).map_err(|e| e.to_string()),
+        }
+        .map_err(|e| format!("Failed to parse config: {}", e))
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct ImportSummary {
+    added: usize,
+    skipped: usize,
+    renamed: usize,
+    overwritten: usize,
+    // "name: reason" for entries refused by the same checks as add_server
+    invalid: Vec<String>,
+    message: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct ServerMetadata {
+    added_at: u64,
+    updated_at: u64,
+}
+
+// Server names a config change touched, sent as the `config-changed` payload so the
+// GUI can update rows incrementally
+#[derive(Debug, Default, Serialize)]
+struct ConfigChange {
+    added: Vec<String>,
+    removed: Vec<String>,
+    updated: Vec<String>,
+    // The server order changed, so the GUI should re-sort rather than patch rows
+    reordered: bool,
+}
+
+impl ConfigChange {
+    fn added(name: &str) -> Self {
+        Self {
+            added: vec![name.to_string()],
+            ..Self::default()
+        }
+    }
+
+    fn removed(name: &str) -> Self {
+        Self {
+            removed: vec![name.to_string()],
+            ..Self::default()
+        }
+    }
+
+    fn updated(name: &str) -> Self {
+        Self {
+            updated: vec![name.to_string()],
+            ..Self::default()
+        }
+    }
+
+    // Compare two configs; moving a server between enabled and disabled counts as
+    // an update
+    fn between(old: &ClaudeConfig, new: &ClaudeConfig) -> Self {
+        let names = |config: &ClaudeConfig| {
+            config
+                .mcp_servers
+                .keys()
+                .chain(config.disabled_servers.keys())
+                .cloned()
+                .collect::<std::collections::BTreeSet<String>>()
+        };
+
+        let (old_names, new_names) = (names(old), names(new));
+        Self {
+            added: new_names.difference(&old_names).cloned().collect(),
+            removed: old_names.difference(&new_names).cloned().collect(),
+            updated: old_names
+                .intersection(&new_names)
+                .filter(|name| old.find_server(name) != new.find_server(name))
+                .cloned()
+                .collect(),
+            ..Self::default()
+        }
+    }
+
+    fn payload(&self) -> serde_json::Value {
+        serde_json::to_value(self).unwrap_or_else(|_| serde_json::json!({}))
+    }
+}
+
+// An env variable some installed server sets or its preset asks for
+#[derive(Debug, Serialize)]
+struct RequiredEnvKey {
+    key: String,
+    used_by: Vec<String>,
+    // False when any server using the key leaves it missing or empty
+    set: bool,
+}
+
+// A port that more than one enabled server is configured to bind
+#[derive(Debug, Serialize)]
+struct PortConflict {
+    port: u16,
+    servers: Vec<String>,
+}
+
+// Servers with identical command, args and env
+#[derive(Debug, Serialize)]
+struct DuplicateGroup {
+    command: String,
+    servers: Vec<String>,
+}
+
+// How one field differs between two versions of a server. Env vars are compared key by
+// key as `env.KEY`, with values shown only as "<redacted>" (or null when unset).
+#[derive(Debug, Serialize)]
+struct ServerFieldChange {
+    field: String,
+    from: serde_json::Value,
+    to: serde_json::Value,
+}
+
+#[derive(Debug, Serialize)]
+struct PresetComparison {
+    name: String,
+    preset_found: bool,
+    matches_preset: bool,
+    // `from` is the installed server, `to` the preset
+    differences: Vec<ServerFieldChange>,
+    message: String,
+}
+
+#[derive(Debug, Serialize)]
+struct PresetInstallStatus {
+    name: String,
+    installed: bool,
+    // Installed command and args equal the preset's; env differences are ignored
+    installed_matches_preset: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct ConfigStats {
+    total_servers: usize,
+    disabled_servers: usize,
+    // Keyed by ServerType name: docker, npx, uvx, uv or other
+    by_type: std::collections::BTreeMap<String, usize>,
+    // Installed servers whose preset of the same name needs API keys
+    requiring_api_keys: usize,
+    total_env_vars: usize,
+}
+
+#[derive(Debug, Serialize)]
+struct BulkDeleteResult {
+    name: String,
+    // "deleted", "not_found", or "read_only" when nothing may be changed
+    status: String,
+}
+
+#[derive(Debug, Serialize)]
+struct SearchResult {
+    name: String,
+    // "preset" or "installed"
+    source: String,
+    category: Option<String>,
+    description: Option<String>,
+    score: f64,
+}
+
+#[derive(Debug, Serialize)]
+struct ServerHealth {
+    name: String,
+    reachable: bool,
+    startup_ms: u64,
+    error: Option<String>,
+}
+
+// Launch-time env for a server; `unresolved` lists `${env:NAME}` references with no OS value
+#[derive(Debug, Serialize)]
+struct ResolvedEnv {
+    env: HashMap<String, String>,
+    unresolved: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct ExpandedArg {
+    raw: String,
+    expanded: String,
+}
+
+// Result of preview_expanded_args; `unresolved` lists variables with no OS value
+#[derive(Debug, Serialize)]
+struct ExpandedArgsPreview {
+    args: Vec<ExpandedArg>,
+    unresolved: Vec<String>,
+}
+
+// What restore_from_backup will do, plus the token that authorizes it
+#[derive(Debug, Serialize)]
+struct RestorePreview {
+    token: String,
+    expires_in_seconds: u64,
+    backup_path: String,
+    added: Vec<String>,
+    removed: Vec<String>,
+    changed: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct ServerValidation {
+    valid: bool,
+    // Likely problems that don't make the config invalid, e.g. a misspelled docker image
+    warnings: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum IssueSeverity {
+    Error,
+    Warning,
+}
+
+// Result of lint_config_file; the file is clean when all three are empty
+#[derive(Debug, Serialize)]
+struct LintReport {
+    path: String,
+    parse_error: Option<JsonErrorInfo>,
+    schema_errors: Vec<SchemaValidationError>,
+    issues: Vec<ConfigIssue>,
+}
+
+#[derive(Debug, Serialize)]
+struct AutofixResult {
+    success: bool,
+    message: String,
+    // The repaired config for preview; unset when there was nothing to fix or the
+    // file is too broken to repair
+    repaired: Option<String>,
+    applied: bool,
+}
+
+// One problem found by validate_full_config
+#[derive(Debug, Serialize)]
+struct ConfigIssue {
+    server: String,
+    severity: IssueSeverity,
+    message: String,
+}
+
+#[derive(Debug, Serialize)]
+struct CommandAvailability {
+    found: bool,
+    resolved_path: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ConfigTarget {
+    pub name: String,
+    pub path: String,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct AppSettings {
     #[serde(rename = "claudeConfigPath")]
@@ -76,6 +602,109 @@ pub struct AppSettings {
     pub mcp_server_port: u16,
     #[serde(rename = "mcpSsePath")]
     pub mcp_sse_path: String,
+    #[serde(rename = "configTargets", default)]
+    pub config_targets: Vec<ConfigTarget>,
+    #[serde(
+        rename = "backupRetentionCount",
+        default = "default_backup_retention_count"
+    )]
+    pub backup_retention_count: usize,
+    #[serde(rename = "secretsInKeychain", default)]
+    pub secrets_in_keychain: bool,
+    #[serde(rename = "mcpBindAddress", default = "default_mcp_bind_address")]
+    pub mcp_bind_address: String,
+    #[serde(rename = "mcpAuthToken", default)]
+    pub mcp_auth_token: Option<String>,
+    #[serde(rename = "mcpSseKeepAliveSecs", default)]
+    pub mcp_sse_keep_alive_secs: Option<u64>,
+    #[serde(rename = "mcpAuditLogPath", default)]
+    pub mcp_audit_log_path: Option<String>,
+    #[serde(
+        rename = "commandCacheTtlSecs",
+        default = "default_command_cache_ttl_secs"
+    )]
+    pub command_cache_ttl_secs: u64,
+    // Path the SSE transport accepts client messages on
+    #[serde(rename = "mcpPostPath", default = "default_mcp_post_path")]
+    pub mcp_post_path: String,
+    // EnvFilter directive for MCP server logs, e.g. "info" or "rmcp=debug"
+    #[serde(rename = "mcpLogLevel", default = "default_mcp_log_level")]
+    pub mcp_log_level: String,
+    // Refuse every config mutation, e.g. for shared or kiosk machines
+    #[serde(rename = "readOnly", default)]
+    pub read_only: bool,
+    // Whether MCP tools list server env key names; values are never exposed
+    #[serde(rename = "mcpExposeEnvKeys", default = "default_true")]
+    pub mcp_expose_env_keys: bool,
+    // When set, MCP tools may only configure servers run by one of these commands
+    #[serde(rename = "mcpCommandAllowlist", default)]
+    pub mcp_command_allowlist: Option<Vec<String>>,
+    #[serde(rename = "mcpTransport", default)]
+    pub mcp_transport: McpTransport,
+}
+
+impl AppSettings {
+    // The configured auth token, treating an empty string as "no token"
+    pub fn auth_token(&self) -> Option<&str> {
+        self.mcp_auth_token
+            .as_deref()
+            .map(str::trim)
+            .filter(|token| !token.is_empty())
+    }
+
+    // Where MCP tool calls are logged; auditing is off when unset or empty
+    pub fn audit_log_path(&self) -> Option<&str> {
+        self.mcp_audit_log_path
+            .as_deref()
+            .map(str::trim)
+            .filter(|path| !path.is_empty())
+    }
+
+    // Reject a command missing from the MCP allowlist; anything passes without one
+    pub fn check_mcp_command(&self, command: &str) -> Result<(), String> {
+        match &self.mcp_command_allowlist {
+            Some(allowlist) if !allowlist.iter().any(|allowed| allowed == command) => Err(format!(
+                "Command '{}' is not in the MCP command allowlist ({})",
+                command,
+                allowlist.join(", ")
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+fn default_mcp_bind_address() -> String {
+    "127.0.0.1".to_string()
+}
+
+fn default_mcp_post_path() -> String {
+    "/message".to_string()
+}
+
+const READ_ONLY_MESSAGE: &str = "read-only mode";
+
+fn read_only_result() -> SaveResult {
+    SaveResult {
+        success: false,
+        message: READ_ONLY_MESSAGE.to_string(),
+        path: None,
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_mcp_log_level() -> String {
+    "info".to_string()
+}
+
+fn default_backup_retention_count() -> usize {
+    10
+}
+
+fn default_command_cache_ttl_secs() -> u64 {
+    30
 }
 
 impl Default for AppSettings {
@@ -86,6 +715,20 @@ impl Default for AppSettings {
             mcp_server_enabled: false,
             mcp_server_port: 8000,
             mcp_sse_path: "/sse".to_string(),
+            config_targets: Vec::new(),
+            backup_retention_count: default_backup_retention_count(),
+            secrets_in_keychain: false,
+            mcp_bind_address: default_mcp_bind_address(),
+            mcp_auth_token: None,
+            mcp_sse_keep_alive_secs: None,
+            mcp_audit_log_path: None,
+            command_cache_ttl_secs: default_command_cache_ttl_secs(),
+            mcp_post_path: default_mcp_post_path(),
+            mcp_log_level: default_mcp_log_level(),
+            read_only: false,
+            mcp_expose_env_keys: true,
+            mcp_command_allowlist: None,
+            mcp_transport: McpTransport::Sse,
         }
     }
 }
@@ -110,14 +753,16 @@ impl ServerType {
             _ => ServerType::Other(command.to_string()),
         }
     }
+}
 
-    fn to_string(&self) -> String {
+impl std::fmt::Display for ServerType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            ServerType::Docker => "docker".to_string(),
-            ServerType::Npx => "npx".to_string(),
-            ServerType::Uvx => "uvx".to_string(),
-            ServerType::Uv => "uv".to_string(),
-            ServerType::Other(s) => s.clone(),
+            ServerType::Docker => f.write_str("docker"),
+            ServerType::Npx => f.write_str("npx"),
+            ServerType::Uvx => f.write_str("uvx"),
+            ServerType::Uv => f.write_str("uv"),
+            ServerType::Other(s) => f.write_str(s),
         }
     }
 }
@@ -129,6 +774,16 @@ pub struct ApiKeyRequirement {
     required: bool,
 }
 
+// A `{{token}}` in a preset's args that is filled in at install time. Placeholders
+// without a default must be supplied by the caller.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ArgPlaceholder {
+    token: String,
+    description: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    default: Option<String>,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 struct PresetServer {
     name: String,
@@ -140,18 +795,70 @@ struct PresetServer {
     args: Vec<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     env: Option<HashMap<String, String>>,
-    #[serde(rename = "apiKeys", skip_serializing_if = "Vec::is_empty")]
+    #[serde(rename = "apiKeys", default, skip_serializing_if = "Vec::is_empty")]
     api_keys: Vec<ApiKeyRequirement>,
-    #[serde(rename = "requiresApiKey")]
+    #[serde(rename = "requiresApiKey", default)]
     requires_api_key: bool,
     // Legacy fields for backward compatibility
     #[serde(rename = "apiKeyName", skip_serializing_if = "Option::is_none")]
     api_key_name: Option<String>,
     #[serde(rename = "apiKeyDescription", skip_serializing_if = "Option::is_none")]
     api_key_description: Option<String>,
+    #[serde(
+        rename = "argPlaceholders",
+        default,
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    arg_placeholders: Vec<ArgPlaceholder>,
 }
 
 impl PresetServer {
+    // Legacy presets set `requiresApiKey`, newer ones only list `apiKeys`
+    fn needs_api_key(&self) -> bool {
+        self.requires_api_key || !self.api_keys.is_empty()
+    }
+
+    // Replace `{{token}}` occurrences in args with the supplied values, falling back
+    // to each placeholder's default
+    fn substitute_arg_placeholders(
+        &self,
+        values: &HashMap<String, String>,
+    ) -> Result<Vec<String>, String> {
+        let mut resolved = Vec::new();
+        let mut missing = Vec::new();
+
+        for placeholder in &self.arg_placeholders {
+            match values
+                .get(&placeholder.token)
+                .or(placeholder.default.as_ref())
+            {
+                Some(value) => resolved.push((format!("{{{{{}}}}}", placeholder.token), value)),
+                None => missing.push(format!(
+                    "{} ({})",
+                    placeholder.token, placeholder.description
+                )),
+            }
+        }
+
+        if !missing.is_empty() {
+            return Err(format!(
+                "Preset '{}' requires values for placeholders: {}",
+                self.name,
+                missing.join(", ")
+            ));
+        }
+
+        Ok(self
+            .args
+            .iter()
+            .map(|arg| {
+                resolved.iter().fold(arg.clone(), |arg, (pattern, value)| {
+                    arg.replace(pattern, value)
+                })
+            })
+            .collect())
+    }
+
     fn validate_command_matches_type(&self) -> bool {
         let expected_type = ServerType::from_command(&self.command);
         self.server_type == expected_type || matches!(self.server_type, ServerType::Other(_))
@@ -168,7 +875,9 @@ fn greet(name: &str) -> String {
 async fn parse_claude_json(
     state: tauri::State<'_, AppState>,
     custom_path: Option<String>,
+    target: Option<String>,
 ) -> Result<Vec<McpServerInfo>, String> {
+    let custom_path = resolve_target_path(&state, target, custom_path).await?;
     internal_parse_claude_json(&state, custom_path).await
 }
 
@@ -178,720 +887,5432 @@ fn get_server_details(name: String, custom_path: Option<String>) -> Result<McpSe
     let file_content = fs::read_to_string(&config_path)
         .map_err(|e| format!("Failed to read Claude Desktop config: {}", e))?;
 
-    let config: ClaudeConfig =
-        serde_json::from_str(&file_content).map_err(|e| format!("Failed to parse JSON: {}", e))?;
+    let config: ClaudeConfig = serde_json::from_str(normalize_config_content(&file_content))
+        .map_err(|e| format!("Failed to parse JSON: {}", e))?;
 
-    let server = config
-        .mcp_servers
-        .get(&name)
-        .ok_or_else(|| format!("Server '{}' not found", name))?;
+    let (server, enabled) = match config.mcp_servers.get(&name) {
+        Some(server) => (server, true),
+        None => config
+            .disabled_servers
+            .get(&name)
+            .map(|server| (server, false))
+            .ok_or_else(|| format!("Server '{}' not found", name))?,
+    };
 
     Ok(McpServerInfo {
         name,
         command: server.command.clone(),
         args: server.args.clone(),
         env: server.env.clone().unwrap_or_default(),
+        cwd: server.cwd.clone(),
+        enabled,
     })
 }
 
 #[tauri::command]
-fn update_server(
+fn resolve_server_secrets(
     name: String,
-    server_data: McpServerEdit,
     custom_path: Option<String>,
-) -> Result<SaveResult, String> {
-    save_server_config(name.clone(), Some(server_data), false, custom_path)
+) -> Result<HashMap<String, String>, String> {
+    let server = get_server_details(name, custom_path)?;
+    resolve_secret_placeholders(&server.env)
 }
 
 #[tauri::command]
-async fn add_server(
+async fn resolve_server_env(
     state: tauri::State<'_, AppState>,
-    app_handle: tauri::AppHandle,
     name: String,
-    server_data: McpServerEdit,
-) -> Result<SaveResult, String> {
-    internal_add_server(&state, name, server_data, Some(&app_handle)).await
+    custom_path: Option<String>,
+) -> Result<ResolvedEnv, String> {
+    let (config, _) = state.load_config(custom_path).await?;
+    let server = config
+        .mcp_servers
+        .get(&name)
+        .or_else(|| config.disabled_servers.get(&name))
+        .ok_or_else(|| format!("Server '{}' not found", name))?;
+
+    resolve_launch_env(&server.env.clone().unwrap_or_default())
 }
 
 #[tauri::command]
-async fn delete_server(
+async fn update_server(
     state: tauri::State<'_, AppState>,
     app_handle: tauri::AppHandle,
     name: String,
-) -> Result<SaveResult, String> {
-    internal_delete_server(&state, name, Some(&app_handle)).await
-}
-
-#[tauri::command]
-fn get_default_config_path() -> Result<String, String> {
-    get_claude_config_path()
+    server_data: McpServerEdit,
+    custom_path: Option<String>,
+) -> Result<SaveResult, AppError> {
+    internal_update_server(&state, name, server_data, custom_path, Some(&app_handle))
+        .await
+        .map_err(AppError::from)
 }
 
 #[tauri::command]
-async fn load_app_settings(state: tauri::State<'_, AppState>) -> Result<AppSettings, String> {
-    let settings_path = get_settings_path()?;
+async fn add_server(
+    state: tauri::State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+    name: String,
+    server_data: McpServerEdit,
+    target: Option<String>,
+    create_if_missing: Option<bool>,
+) -> Result<SaveResult, AppError> {
+    let custom_path = resolve_target_path(&state, target, None).await?;
+    internal_add_server(
+        &state,
+        name,
+        server_data,
+        custom_path,
+        create_if_missing.unwrap_or(true),
+        Some(&app_handle),
+    )
+    .await
+    .map_err(AppError::from)
+}
 
-    let settings = if !Path::new(&settings_path).exists() {
-        // Return default settings if file doesn't exist
-        AppSettings::default()
-    } else {
-        let file_content = fs::read_to_string(&settings_path)
-            .map_err(|e| format!("Failed to read settings file: {}", e))?;
+#[derive(Debug, Serialize)]
+struct EnvFileAddResult {
+    success: bool,
+    message: String,
+    // Names only; values never leave the backend
+    loaded_keys: Vec<String>,
+}
 
-        serde_json::from_str(&file_content)
-            .map_err(|e| format!("Failed to parse settings: {}", e))?
-    };
+// Add a server with env vars read from a `.env`-format file. Entries in
+// `server_data.env` take precedence over the file.
+#[tauri::command]
+async fn add_server_with_env_file(
+    state: tauri::State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+    name: String,
+    mut server_data: McpServerEdit,
+    env_file_path: String,
+    target: Option<String>,
+) -> Result<EnvFileAddResult, AppError> {
+    let content = fs::read_to_string(&env_file_path).map_err(|e| AppError::Io {
+        path: Some(env_file_path.clone()),
+        message: format!("Failed to read env file: {}", e),
+    })?;
+    let entries = parse_env_file(&content).map_err(|message| AppError::Parse {
+        message,
+        details: None,
+    })?;
+
+    let mut loaded_keys: Vec<String> = entries.iter().map(|(key, _)| key.clone()).collect();
+    loaded_keys.sort();
+    loaded_keys.dedup();
+
+    let explicit_env = std::mem::take(&mut server_data.env);
+    server_data.env = entries.into_iter().collect();
+    server_data.env.extend(explicit_env);
+
+    let custom_path = resolve_target_path(&state, target, None).await?;
+    let result = internal_add_server(
+        &state,
+        name,
+        server_data,
+        custom_path,
+        true,
+        Some(&app_handle),
+    )
+    .await
+    .map_err(AppError::from)?;
+
+    Ok(EnvFileAddResult {
+        success: result.success,
+        message: result.message,
+        loaded_keys: if result.success {
+            loaded_keys
+        } else {
+            Vec::new()
+        },
+    })
+}
 
-    // Update the settings cache
-    {
-        let mut settings_cache = state.settings_cache.write().await;
-        *settings_cache = settings.clone();
-    }
+#[tauri::command]
+async fn install_preset_server(
+    state: tauri::State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+    preset_name: String,
+    api_keys: Option<HashMap<String, String>>,
+    placeholders: Option<HashMap<String, String>>,
+    pull_image: Option<bool>,
+    on_conflict: Option<mcp_server::OnConflict>,
+) -> Result<SaveResult, AppError> {
+    internal_install_preset(
+        &state,
+        preset_name,
+        api_keys.unwrap_or_default(),
+        placeholders.unwrap_or_default(),
+        pull_image.unwrap_or(false),
+        on_conflict.unwrap_or_default(),
+        Some(&app_handle),
+    )
+    .await
+    .map_err(AppError::from)
+}
 
-    Ok(settings)
+#[tauri::command]
+async fn delete_server(
+    state: tauri::State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+    name: String,
+    target: Option<String>,
+) -> Result<SaveResult, AppError> {
+    let custom_path = resolve_target_path(&state, target, None).await?;
+    internal_delete_server(&state, name, custom_path, Some(&app_handle))
+        .await
+        .map_err(AppError::from)
 }
 
 #[tauri::command]
-async fn save_app_settings(
+async fn delete_servers(
     state: tauri::State<'_, AppState>,
-    settings: AppSettings,
-) -> Result<SaveResult, String> {
-    let settings_path = get_settings_path()?;
-    let settings_dir = Path::new(&settings_path)
-        .parent()
-        .ok_or("Could not determine settings directory")?;
+    app_handle: tauri::AppHandle,
+    names: Vec<String>,
+) -> Result<Vec<BulkDeleteResult>, String> {
+    internal_delete_servers(&state, names, Some(&app_handle)).await
+}
 
-    // Create settings directory if it doesn't exist
-    if !settings_dir.exists() {
-        fs::create_dir_all(settings_dir)
-            .map_err(|e| format!("Failed to create settings directory: {}", e))?;
+#[tauri::command]
+async fn get_config_stats(
+    state: tauri::State<'_, AppState>,
+    custom_path: Option<String>,
+) -> Result<ConfigStats, String> {
+    let servers = internal_parse_claude_json(&state, custom_path).await?;
+    let presets = get_preset_servers_database();
+
+    let mut by_type = std::collections::BTreeMap::new();
+    for server in &servers {
+        let server_type = match ServerType::from_command(&server.command) {
+            ServerType::Other(_) => "other".to_string(),
+            known => known.to_string(),
+        };
+        *by_type.entry(server_type).or_insert(0) += 1;
     }
 
-    let settings_json = serde_json::to_string_pretty(&settings)
-        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
-
-    fs::write(&settings_path, settings_json)
-        .map_err(|e| format!("Failed to write settings file: {}", e))?;
+    let requiring_api_keys = servers
+        .iter()
+        .filter(|server| {
+            presets
+                .iter()
+                .any(|preset| preset.name == server.name && preset.needs_api_key())
+        })
+        .count();
+
+    Ok(ConfigStats {
+        total_servers: servers.len(),
+        disabled_servers: servers.iter().filter(|server| !server.enabled).count(),
+        by_type,
+        requiring_api_keys,
+        total_env_vars: servers.iter().map(|server| server.env.len()).sum(),
+    })
+}
 
-    // Update the settings cache
-    {
-        let mut settings_cache = state.settings_cache.write().await;
-        *settings_cache = settings;
-    }
+// Claude Desktop starts every enabled server at launch, so configs past these sizes
+// tend to slow it down noticeably
+const LARGE_CONFIG_SERVERS: usize = 50;
+const HUGE_CONFIG_SERVERS: usize = 200;
+const LARGE_CONFIG_BYTES: usize = 256 * 1024;
+const HUGE_CONFIG_BYTES: usize = 1024 * 1024;
 
-    Ok(SaveResult {
-        success: true,
-        message: "Settings saved successfully".to_string(),
-    })
+#[derive(Debug, Serialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+enum ConfigSizeLevel {
+    Ok,
+    Large,
+    Huge,
 }
 
-#[tauri::command]
-fn get_preset_servers() -> Vec<PresetServer> {
-    get_preset_servers_database()
+#[derive(Debug, Serialize)]
+struct ConfigSizeSummary {
+    // Enabled servers only; disabled ones are never started
+    server_count: usize,
+    // Size of the config as the next save would write it
+    byte_size: usize,
+    level: ConfigSizeLevel,
 }
 
 #[tauri::command]
-fn get_preset_servers_by_category(category: String) -> Vec<PresetServer> {
-    get_preset_servers_database()
-        .into_iter()
-        .filter(|server| server.category == category)
-        .collect()
+async fn get_config_size_warning(
+    state: tauri::State<'_, AppState>,
+    custom_path: Option<String>,
+) -> Result<ConfigSizeSummary, String> {
+    internal_get_config_size(&state, custom_path).await
 }
 
-#[tauri::command]
-fn get_preset_server_categories() -> Vec<String> {
-    let mut categories: Vec<String> = get_preset_servers_database()
-        .into_iter()
-        .map(|server| server.category)
-        .collect();
+async fn internal_get_config_size(
+    state: &AppState,
+    custom_path: Option<String>,
+) -> Result<ConfigSizeSummary, String> {
+    let (config, config_path) = state.load_config(custom_path).await?;
+    let byte_size = serialize_config_preserving_keys(&config_path, &config)?.len();
+    let server_count = config.mcp_servers.len();
+
+    let level_for = |value: usize, large: usize, huge: usize| {
+        if value >= huge {
+            ConfigSizeLevel::Huge
+        } else if value >= large {
+            ConfigSizeLevel::Large
+        } else {
+            ConfigSizeLevel::Ok
+        }
+    };
+    let by_servers = level_for(server_count, LARGE_CONFIG_SERVERS, HUGE_CONFIG_SERVERS);
+    let by_bytes = level_for(byte_size, LARGE_CONFIG_BYTES, HUGE_CONFIG_BYTES);
 
-    // Remove duplicates and sort
-    categories.sort();
-    categories.dedup();
-    categories
+    Ok(ConfigSizeSummary {
+        server_count,
+        byte_size,
+        level: by_servers.max(by_bytes),
+    })
 }
 
+// Every problem across all enabled servers, instead of stopping at the first one
+// like validate_claude_config_structure does
 #[tauri::command]
-fn get_preset_server_by_name(name: String) -> Option<PresetServer> {
-    get_preset_servers_database()
-        .into_iter()
-        .find(|server| server.name == name)
+async fn validate_full_config(
+    state: tauri::State<'_, AppState>,
+    custom_path: Option<String>,
+) -> Result<Vec<ConfigIssue>, String> {
+    let (config, _) = state.load_config(custom_path).await?;
+    Ok(config_issues(&state, &config).await)
 }
 
-#[tauri::command]
-fn get_preset_servers_by_type(server_type: String) -> Vec<PresetServer> {
-    let target_type = match server_type.to_lowercase().as_str() {
-        "docker" => ServerType::Docker,
-        "npx" => ServerType::Npx,
-        "uvx" => ServerType::Uvx,
-        "uv" => ServerType::Uv,
-        _ => ServerType::Other(server_type),
-    };
+async fn config_issues(state: &AppState, config: &ClaudeConfig) -> Vec<ConfigIssue> {
+    let mut issues = Vec::new();
 
-    get_preset_servers_database()
-        .into_iter()
-        .filter(|server| server.server_type == target_type)
-        .collect()
+    for (name, server) in &config.mcp_servers {
+        let mut issue = |severity, message: String| {
+            issues.push(ConfigIssue {
+                server: name.clone(),
+                severity,
+                message,
+            })
+        };
+
+        if name.trim().is_empty() {
+            issue(
+                IssueSeverity::Error,
+                "Server name cannot be empty".to_string(),
+            );
+        }
+
+        if server.command.trim().is_empty() {
+            issue(IssueSeverity::Error, "Command is empty".to_string());
+        } else if server.command.contains(' ') && !server.command.starts_with('"') {
+            issue(
+                IssueSeverity::Error,
+                "Command contains spaces but is not quoted. Consider moving arguments to the 'args' array".to_string(),
+            );
+        } else if state
+            .resolve_command_cached(&server.command)
+            .await
+            .is_none()
+        {
+            issue(
+                IssueSeverity::Warning,
+                format!("Command '{}' was not found on PATH", server.command),
+            );
+        }
+
+        let mut invalid_keys: Vec<&String> = server
+            .env
+            .iter()
+            .flatten()
+            .map(|(key, _)| key)
+            .filter(|key| !is_valid_env_key(key))
+            .collect();
+        invalid_keys.sort();
+        for key in invalid_keys {
+            issue(
+                IssueSeverity::Error,
+                format!("Invalid environment variable name '{}'", key),
+            );
+        }
+
+        for message in validate_arg_style(&server.args) {
+            issue(IssueSeverity::Warning, message);
+        }
+    }
+
+    issues
 }
 
-#[tauri::command]
-fn get_server_types() -> Vec<String> {
-    let mut types: Vec<String> = get_preset_servers_database()
-        .into_iter()
-        .map(|server| server.server_type.to_string())
-        .collect();
+// Advisory checks for common copy-paste mistakes in args: mixing `--flag=value` with
+// `--flag value` in one server, and a single arg that looks like a whole command line.
+// Only `--long` options count towards the mix; short flags like docker's `-i` are left
+// alone. Args naming an existing path are allowed to contain spaces.
+fn validate_arg_style(args: &[String]) -> Vec<String> {
+    let mut warnings = Vec::new();
+
+    let joined = args
+        .iter()
+        .filter(|arg| arg.starts_with("--") && arg.contains('='))
+        .count();
+    let separate = args
+        .windows(2)
+        .filter(|pair| {
+            pair[0].starts_with("--") && !pair[0].contains('=') && !pair[1].starts_with('-')
+        })
+        .count();
+    if joined > 0 && separate > 0 {
+        warnings.push(
+            "Args mix '--flag=value' and '--flag value' styles; some servers only accept one"
+                .to_string(),
+        );
+    }
 
-    // Remove duplicates and sort
-    types.sort();
-    types.dedup();
-    types
+    for arg in args {
+        if arg.trim().contains(char::is_whitespace) && !Path::new(arg).exists() {
+            warnings.push(format!(
+                "Argument '{}' contains spaces; if it holds several arguments, split them into separate 'args' entries",
+                arg
+            ));
+        }
+    }
+
+    warnings
 }
 
+// Check any config file, e.g. a backup or another client's config, without loading
+// it into AppState. Later stages only run when the earlier ones pass.
 #[tauri::command]
-fn validate_server_config(server: PresetServer) -> bool {
-    server.validate_command_matches_type()
-}
+async fn lint_config_file(
+    state: tauri::State<'_, AppState>,
+    path: String,
+) -> Result<LintReport, String> {
+    let path = expand_user_path(path.trim())?;
+    let file_content = fs::read_to_string(&path)
+        .map_err(|e| ConfigIoError::from_io_error(&path, &e).to_error_string())?;
+    let file_content = normalize_config_content(&file_content);
+
+    let mut report = LintReport {
+        path: path.clone(),
+        parse_error: None,
+        schema_errors: Vec::new(),
+        issues: Vec::new(),
+    };
 
-// Internal function for starting MCP server (used by both Tauri command and auto-start)
-async fn internal_start_mcp_server(state: &AppState) -> Result<SaveResult, String> {
-    let settings = {
-        let settings_guard = state.settings_cache.read().await;
-        settings_guard.clone()
+    let raw_config: serde_json::Value = match serde_json::from_str(file_content) {
+        Ok(raw_config) => raw_config,
+        Err(e) => {
+            let mut error_info = analyze_json_error(file_content, &e);
+            error_info.has_backup = Path::new(&format!("{}.backup", path)).exists();
+            report.parse_error = Some(error_info);
+            return Ok(report);
+        }
     };
 
-    if !settings.mcp_server_enabled {
-        return Ok(SaveResult {
-            success: false,
-            message: "MCP server is disabled in settings".to_string(),
+    report.schema_errors = validate_against_schema(&raw_config)?;
+    if !report.schema_errors.is_empty() {
+        return Ok(report);
+    }
+
+    let config: ClaudeConfig =
+        serde_json::from_value(raw_config).map_err(|e| format!("Failed to parse config: {}", e))?;
+    report.issues = config_issues(&state, &config).await;
+
+    Ok(report)
+}
+
+// Repair a config that is almost valid JSON (trailing commas, `//` and `/* */`
+// comments) by reading it leniently as JSON5. Only writes the repair, after a backup,
+// when `apply` is true.
+#[tauri::command]
+async fn try_autofix_json(
+    state: tauri::State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+    custom_path: Option<String>,
+    apply: Option<bool>,
+) -> Result<AutofixResult, String> {
+    let config_path = resolve_config_path(custom_path)?;
+    let original = fs::read_to_string(&config_path)
+        .map_err(|e| ConfigIoError::from_io_error(&config_path, &e).to_error_string())?;
+    let content = normalize_config_content(&original);
+
+    if serde_json::from_str::<serde_json::Value>(content).is_ok() {
+        return Ok(AutofixResult {
+            success: true,
+            message: "Config is already valid JSON".to_string(),
+            repaired: None,
+            applied: false,
         });
     }
 
-    // Check if server is already running
-    {
-        let status_guard = state.mcp_server_status.read().await;
-        if status_guard.running {
-            return Ok(SaveResult {
+    let value: serde_json::Value = match json5::from_str(content) {
+        Ok(value) => value,
+        Err(e) => {
+            return Ok(AutofixResult {
                 success: false,
-                message: "MCP server is already running".to_string(),
+                message: format!("Config could not be repaired automatically: {}", e),
+                repaired: None,
+                applied: false,
             });
         }
+    };
+    let repaired = serde_json::to_string_pretty(&value)
+        .map_err(|e| format!("Failed to serialize config: {}", e))?;
+
+    if !apply.unwrap_or(false) {
+        return Ok(AutofixResult {
+            success: true,
+            message: "Repaired config ready for preview; pass apply to write it".to_string(),
+            repaired: Some(repaired),
+            applied: false,
+        });
     }
 
-    // Validate port availability (basic check)
-    if let Err(_) = std::net::TcpListener::bind(format!("127.0.0.1:{}", settings.mcp_server_port)) {
-        return Ok(SaveResult {
+    if state.is_read_only().await {
+        return Ok(AutofixResult {
             success: false,
-            message: format!("Port {} is already in use", settings.mcp_server_port),
+            message: READ_ONLY_MESSAGE.to_string(),
+            repaired: Some(repaired),
+            applied: false,
         });
     }
 
-    // Create cancellation token
-    let cancellation_token = CancellationToken::new();
-    
-    // Store cancellation token
-    {
-        let mut token_guard = state.mcp_server_cancellation.write().await;
-        *token_guard = Some(cancellation_token.clone());
+    // Valid JSON can still be an invalid config; don't write a repair Claude would reject
+    let schema_errors = validate_against_schema(&value)?;
+    if !schema_errors.is_empty() {
+        let details: Vec<String> = schema_errors
+            .iter()
+            .map(|error| format!("{} at {}", error.message, error.path))
+            .collect();
+        return Ok(AutofixResult {
+            success: false,
+            message: format!("Repaired config fails validation: {}", details.join("; ")),
+            repaired: Some(repaired),
+            applied: false,
+        });
     }
+    let repaired_config: ClaudeConfig =
+        serde_json::from_value(value).map_err(|e| format!("Failed to parse config: {}", e))?;
 
-    // Update server status
-    {
-        let mut status_guard = state.mcp_server_status.write().await;
-        status_guard.running = true;
-        status_guard.port = Some(settings.mcp_server_port);
-        status_guard.sse_path = Some(settings.mcp_sse_path.clone());
-        status_guard.url = Some(format!("http://127.0.0.1:{}{}", settings.mcp_server_port, settings.mcp_sse_path));
-    }
+    let _write_guard = state.lock_config_writes().await;
+    let retention_count = state.settings_cache.read().await.backup_retention_count;
+    create_backup(&config_path, retention_count)?;
+    write_config_atomically(&config_path, &repaired)
+        .map_err(|e| format!("Failed to write config: {}", e))?;
 
-    // Start MCP server in background
-    let state_clone = state.clone();
-    tokio::spawn(async move {
-        if let Err(e) = mcp_server::start_mcp_server(state_clone.clone()).await {
-            eprintln!("MCP server error: {}", e);
-            // Reset status on error
-            let mut status_guard = state_clone.mcp_server_status.write().await;
-            status_guard.running = false;
-            status_guard.port = None;
-            status_guard.sse_path = None;
-            status_guard.url = None;
-        }
-    });
+    // The broken file can't be read, so compare against the last config loaded from it
+    let previous = state
+        .config_cache
+        .write()
+        .await
+        .insert(config_path.clone(), repaired_config.clone())
+        .unwrap_or_default();
+
+    state
+        .push_undo_snapshot(ConfigSnapshot {
+            operation: "Auto-fix JSON".to_string(),
+            config_path: config_path.clone(),
+            content: original,
+        })
+        .await;
+    state
+        .emit_event(
+            &app_handle,
+            "config-changed",
+            ConfigChange::between(&previous, &repaired_config).payload(),
+        )
+        .await;
 
-    Ok(SaveResult {
+    Ok(AutofixResult {
         success: true,
-        message: format!("MCP server started on port {}", settings.mcp_server_port),
+        message: "Config repaired; the original was backed up".to_string(),
+        repaired: Some(repaired),
+        applied: true,
     })
 }
 
+// Exactly what the next save would write for the current config, unknown top-level
+// keys included, without touching the file
 #[tauri::command]
-async fn start_mcp_server(state: tauri::State<'_, AppState>) -> Result<SaveResult, String> {
-    let settings = {
-        let settings_guard = state.settings_cache.read().await;
-        settings_guard.clone()
-    };
-
-    println!("Debug: MCP server enabled: {}", settings.mcp_server_enabled);
-    println!("Debug: MCP server port: {}", settings.mcp_server_port);
-    println!("Debug: MCP SSE path: {}", settings.mcp_sse_path);
-
-    internal_start_mcp_server(state.inner()).await
+async fn preview_config_output(
+    state: tauri::State<'_, AppState>,
+    custom_path: Option<String>,
+) -> Result<String, String> {
+    let (config, config_path) = state.load_config(custom_path).await?;
+    serialize_config_preserving_keys(&config_path, &config)
 }
 
+// A single server as the pretty-printed `{ "<name>": { ... } }` object it would be
+// under `mcpServers`, optionally with env values blanked for sharing
 #[tauri::command]
-async fn stop_mcp_server(state: tauri::State<'_, AppState>) -> Result<SaveResult, String> {
-    // Get and cancel the token
-    let token = {
-        let mut token_guard = state.mcp_server_cancellation.write().await;
-        token_guard.take()
-    };
-
-    if let Some(token) = token {
-        token.cancel();
-    }
+async fn get_server_as_json(
+    state: tauri::State<'_, AppState>,
+    name: String,
+    redact_env: Option<bool>,
+    custom_path: Option<String>,
+) -> Result<String, String> {
+    let (config, _) = state.load_config(custom_path).await?;
+    let mut server = config
+        .mcp_servers
+        .get(&name)
+        .or(config.disabled_servers.get(&name))
+        .cloned()
+        .ok_or_else(|| format!("Server '{}' not found", name))?;
 
-    // Update server status
-    {
-        let mut status_guard = state.mcp_server_status.write().await;
-        status_guard.running = false;
-        status_guard.port = None;
-        status_guard.sse_path = None;
-        status_guard.url = None;
+    if redact_env.unwrap_or(false) {
+        if let Some(env) = server.env.as_mut() {
+            env.values_mut().for_each(String::clear);
+        }
     }
 
-    Ok(SaveResult {
-        success: true,
-        message: "MCP server stopped".to_string(),
-    })
+    let mut entry = IndexMap::new();
+    entry.insert(name, server);
+    serde_json::to_string_pretty(&entry).map_err(|e| format!("Failed to serialize server: {}", e))
 }
 
+// Installed server names bucketed by the category of the preset with the same name
 #[tauri::command]
-async fn get_mcp_server_status(state: tauri::State<'_, AppState>) -> Result<McpServerStatus, String> {
-    let status_guard = state.mcp_server_status.read().await;
-    Ok(status_guard.clone())
+async fn group_installed_by_category(
+    state: tauri::State<'_, AppState>,
+    custom_path: Option<String>,
+) -> Result<std::collections::BTreeMap<String, Vec<String>>, String> {
+    let servers = internal_parse_claude_json(&state, custom_path).await?;
+    let presets = get_preset_servers_database();
+
+    let mut groups: std::collections::BTreeMap<String, Vec<String>> =
+        std::collections::BTreeMap::new();
+    for server in servers {
+        let category = presets
+            .iter()
+            .find(|preset| preset.name == server.name)
+            .map(|preset| preset.category.clone())
+            .unwrap_or_else(|| "Uncategorized".to_string());
+        groups.entry(category).or_default().push(server.name);
+    }
+
+    Ok(groups)
 }
 
+// Installed servers whose command classifies as `server_type`. Anything other than
+// docker/npx/uvx/uv is compared with the raw command, so "node" finds node servers.
 #[tauri::command]
-fn validate_mcp_port(port: u16) -> Result<SaveResult, String> {
-    if port < 1024 {
-        return Ok(SaveResult {
-            success: false,
-            message: "Port must be between 1024 and 65535".to_string(),
-        });
-    }
+async fn get_installed_servers_by_type(
+    state: tauri::State<'_, AppState>,
+    server_type: String,
+    custom_path: Option<String>,
+) -> Result<Vec<McpServerInfo>, String> {
+    internal_get_installed_servers_by_type(&state, &server_type, custom_path).await
+}
 
-    // Try to bind to the port to check availability
-    match std::net::TcpListener::bind(format!("127.0.0.1:{}", port)) {
-        Ok(_) => Ok(SaveResult {
-            success: true,
-            message: format!("Port {} is available", port),
-        }),
-        Err(_) => Ok(SaveResult {
-            success: false,
-            message: format!("Port {} is already in use", port),
-        }),
+async fn internal_get_installed_servers_by_type(
+    state: &AppState,
+    server_type: &str,
+    custom_path: Option<String>,
+) -> Result<Vec<McpServerInfo>, String> {
+    let target_type = ServerType::from_command(server_type);
+    let servers = internal_parse_claude_json(state, custom_path).await?;
+
+    Ok(servers
+        .into_iter()
+        .filter(|server| ServerType::from_command(&server.command) == target_type)
+        .collect())
+}
+
+#[tauri::command]
+async fn list_required_env_keys(
+    state: tauri::State<'_, AppState>,
+    custom_path: Option<String>,
+) -> Result<Vec<RequiredEnvKey>, String> {
+    let servers = internal_parse_claude_json(&state, custom_path).await?;
+    let presets = get_preset_servers_database();
+
+    let mut keys: std::collections::BTreeMap<String, RequiredEnvKey> =
+        std::collections::BTreeMap::new();
+    for server in &servers {
+        let preset_keys = presets
+            .iter()
+            .filter(|preset| preset.name == server.name)
+            .flat_map(|preset| {
+                preset
+                    .api_keys
+                    .iter()
+                    .map(|api_key| api_key.name.clone())
+                    .chain(preset.api_key_name.clone())
+            });
+        let mut server_keys: Vec<String> = server.env.keys().cloned().chain(preset_keys).collect();
+        server_keys.sort();
+        server_keys.dedup();
+
+        for key in server_keys {
+            let is_set = server
+                .env
+                .get(&key)
+                .is_some_and(|value| !value.trim().is_empty());
+            let entry = keys.entry(key.clone()).or_insert_with(|| RequiredEnvKey {
+                key,
+                used_by: Vec::new(),
+                set: true,
+            });
+            entry.used_by.push(server.name.clone());
+            entry.set &= is_set;
+        }
     }
+
+    Ok(keys.into_values().collect())
 }
 
 #[tauri::command]
-fn get_backup_info(custom_path: Option<String>) -> Result<Option<BackupInfo>, String> {
-    let config_path = resolve_config_path(custom_path)?;
-    let backup_path = format!("{}.backup", config_path);
+async fn detect_port_conflicts(
+    state: tauri::State<'_, AppState>,
+    custom_path: Option<String>,
+) -> Result<Vec<PortConflict>, String> {
+    let (config, _) = state.load_config(custom_path).await?;
 
-    if !Path::new(&backup_path).exists() {
-        return Ok(None);
+    let mut by_port: std::collections::BTreeMap<u16, Vec<String>> =
+        std::collections::BTreeMap::new();
+    for (name, server) in &config.mcp_servers {
+        let mut ports = server_ports(server);
+        ports.sort_unstable();
+        ports.dedup();
+        for port in ports {
+            by_port.entry(port).or_default().push(name.clone());
+        }
     }
 
-    let metadata =
-        fs::metadata(&backup_path).map_err(|e| format!("Failed to get backup metadata: {}", e))?;
+    Ok(by_port
+        .into_iter()
+        .filter(|(_, servers)| servers.len() > 1)
+        .map(|(port, servers)| PortConflict { port, servers })
+        .collect())
+}
 
-    let size = metadata.len();
-    let created = metadata
-        .modified()
-        .map(|time| {
-            let duration = time
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap_or_default();
-            let datetime = std::time::SystemTime::UNIX_EPOCH + duration;
-            format!("{:?}", datetime) // Simple formatting for now
-        })
-        .unwrap_or_else(|_| "Unknown".to_string());
+// Ports a server is likely to bind, from `--port N`, `--port=N`, `-p N` (the host side
+// of a docker `-p host:container` mapping) and a `PORT` env var
+fn server_ports(server: &McpServer) -> Vec<u16> {
+    let mut ports = Vec::new();
+
+    let mut args = server.args.iter();
+    while let Some(arg) = args.next() {
+        let value = match arg.split_once('=') {
+            Some(("--port" | "-p", value)) => Some(value),
+            _ if arg == "--port" || arg == "-p" => args.next().map(String::as_str),
+            _ => None,
+        };
 
-    // Validate backup by trying to parse it
-    let is_valid = match fs::read_to_string(&backup_path) {
-        Ok(content) => serde_json::from_str::<ClaudeConfig>(&content).is_ok(),
-        Err(_) => false,
-    };
+        // For `[ip:]host:container` mappings the host port is second to last
+        let host_port = value.and_then(|value| {
+            let parts: Vec<&str> = value.split(':').collect();
+            let host = if parts.len() > 1 {
+                parts[parts.len() - 2]
+            } else {
+                parts[0]
+            };
+            host.parse().ok()
+        });
+        ports.extend(host_port);
+    }
 
-    Ok(Some(BackupInfo {
-        path: backup_path,
-        created,
-        size,
-        is_valid,
-    }))
+    if let Some(port) = server
+        .env
+        .as_ref()
+        .and_then(|env| env.get("PORT"))
+        .and_then(|port| port.trim().parse().ok())
+    {
+        ports.push(port);
+    }
+
+    ports
 }
 
 #[tauri::command]
-fn restore_from_backup(custom_path: Option<String>) -> Result<SaveResult, String> {
-    let config_path = resolve_config_path(custom_path)?;
-    let backup_path = format!("{}.backup", config_path);
+async fn find_duplicate_servers(
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<DuplicateGroup>, String> {
+    let (config, _) = state.load_config(None).await?;
+    Ok(group_duplicate_servers(&config))
+}
 
-    if !Path::new(&backup_path).exists() {
+// Keep the alphabetically first server of each duplicate group and delete the rest
+#[tauri::command]
+async fn merge_duplicates(
+    state: tauri::State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+) -> Result<SaveResult, String> {
+    if state.is_read_only().await {
+        return Ok(read_only_result());
+    }
+
+    // Group and delete under one lock, so a concurrent edit can't change which servers
+    // are duplicates in between
+    let _write_guard = state.lock_config_writes().await;
+    let (config, config_path) = state.load_config(None).await?;
+    let to_remove: Vec<String> = group_duplicate_servers(&config)
+        .into_iter()
+        .flat_map(|group| group.servers.into_iter().skip(1))
+        .collect();
+
+    if to_remove.is_empty() {
         return Ok(SaveResult {
-            success: false,
-            message: "No backup file found".to_string(),
+            success: true,
+            message: "No duplicate servers found".to_string(),
+            path: None,
         });
     }
 
-    // Validate backup before restoring
-    let backup_content = fs::read_to_string(&backup_path)
-        .map_err(|e| format!("Failed to read backup file: {}", e))?;
-
-    let _config: ClaudeConfig =
-        serde_json::from_str(&backup_content).map_err(|_| "Backup file is corrupted or invalid")?;
+    let results =
+        remove_servers(&state, config, &config_path, to_remove, Some(&app_handle)).await?;
+    let names_with = |status: &str| {
+        results
+            .iter()
+            .filter(|result| result.status == status)
+            .map(|result| result.name.as_str())
+            .collect::<Vec<_>>()
+    };
+    let (deleted, not_found) = (names_with("deleted"), names_with("not_found"));
 
-    // Create a backup of the current (potentially broken) file
-    let broken_backup_path = format!("{}.broken", config_path);
-    if Path::new(&config_path).exists() {
-        fs::copy(&config_path, &broken_backup_path)
-            .map_err(|e| format!("Failed to backup current file: {}", e))?;
+    let mut message = format!("Removed duplicate servers: {}", deleted.join(", "));
+    if !not_found.is_empty() {
+        message.push_str(&format!("; not found: {}", not_found.join(", ")));
     }
+    Ok(SaveResult {
+        success: not_found.is_empty(),
+        message,
+        path: Some(config_path),
+    })
+}
 
-    // Restore from backup
-    fs::copy(&backup_path, &config_path)
-        .map_err(|e| format!("Failed to restore from backup: {}", e))?;
+// Rewrite the config with servers in alphabetical order
+#[tauri::command]
+async fn sort_config_servers(
+    state: tauri::State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+    custom_path: Option<String>,
+) -> Result<SaveResult, String> {
+    let _write_guard = state.lock_config_writes().await;
+    let (mut config, config_path) = state.load_config(custom_path).await?;
+    config.mcp_servers.sort_keys();
+    config.disabled_servers.sort_keys();
+
+    state
+        .save_config(&config_path, &config, "Sort servers")
+        .await?;
+    state
+        .emit_event(
+            &app_handle,
+            "config-changed",
+            ConfigChange {
+                reordered: true,
+                ..ConfigChange::default()
+            }
+            .payload(),
+        )
+        .await;
 
     Ok(SaveResult {
         success: true,
-        message: "Configuration restored from backup successfully".to_string(),
+        message: "Servers sorted by name".to_string(),
+        path: Some(config_path.clone()),
     })
 }
 
 #[tauri::command]
-fn open_file_location(path: String) -> Result<(), String> {
-    use std::process::Command;
-
-    #[cfg(target_os = "windows")]
-    {
-        Command::new("explorer")
-            .args(["/select,", &path])
-            .spawn()
-            .map_err(|e| format!("Failed to open file location: {}", e))?;
-    }
-
-    #[cfg(target_os = "macos")]
-    {
-        Command::new("open")
-            .args(["-R", &path])
-            .spawn()
-            .map_err(|e| format!("Failed to open file location: {}", e))?;
-    }
-
-    #[cfg(target_os = "linux")]
-    {
-        // Try different file managers for Linux
-        let file_managers = ["nautilus", "dolphin", "thunar", "pcmanfm", "nemo"];
-        let mut success = false;
+async fn rename_server(
+    state: tauri::State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+    old_name: String,
+    new_name: String,
+) -> Result<SaveResult, String> {
+    internal_rename_server(&state, old_name, new_name, Some(&app_handle)).await
+}
 
-        for manager in &file_managers {
-            if let Ok(_) = Command::new(manager).arg(&path).spawn() {
-                success = true;
-                break;
-            }
-        }
+#[tauri::command]
+async fn duplicate_server(
+    state: tauri::State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+    source_name: String,
+    new_name: String,
+) -> Result<SaveResult, String> {
+    internal_duplicate_server(&state, source_name, new_name, Some(&app_handle)).await
+}
 
-        if !success {
-            // Fallback to opening the parent directory with xdg-open
-            let parent_path = std::path::Path::new(&path)
-                .parent()
-                .unwrap_or_else(|| std::path::Path::new("/"))
-                .to_string_lossy();
+#[tauri::command]
+async fn import_servers_from_json(
+    state: tauri::State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+    json: String,
+    conflict_strategy: ConflictStrategy,
+) -> Result<ImportSummary, String> {
+    internal_import_servers_from_json(&state, json, conflict_strategy, Some(&app_handle)).await
+}
 
-            Command::new("xdg-open")
-                .arg(parent_path.as_ref())
-                .spawn()
-                .map_err(|e| format!("Failed to open file location: {}", e))?;
-        }
+#[tauri::command]
+async fn export_config_as(
+    state: tauri::State<'_, AppState>,
+    format: ConfigFormat,
+    output_path: Option<String>,
+) -> Result<String, String> {
+    let (config, _) = state.load_config(None).await?;
+    let exported = format.serialize(&config)?;
+
+    if let Some(path) = output_path.filter(|path| !path.trim().is_empty()) {
+        fs::write(&path, &exported).map_err(|e| format!("Failed to write export: {}", e))?;
     }
 
-    Ok(())
+    Ok(exported)
 }
 
+// Replace the configured servers with the ones in `content`, e.g. a hand-edited YAML export
 #[tauri::command]
-fn create_manual_backup(custom_path: Option<String>) -> Result<SaveResult, String> {
-    let config_path = resolve_config_path(custom_path)?;
+async fn import_config_from(
+    state: tauri::State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+    content: String,
+    format: ConfigFormat,
+) -> Result<SaveResult, String> {
+    let imported = format.deserialize(&content)?;
 
-    if !Path::new(&config_path).exists() {
+    // Run the imported config through the same checks as a config read from disk
+    let raw_config = serde_json::to_value(&imported)
+        .map_err(|e| format!("Failed to serialize config: {}", e))?;
+    let schema_errors = validate_against_schema(&raw_config)?;
+    if !schema_errors.is_empty() {
+        let details: Vec<String> = schema_errors
+            .iter()
+            .map(|error| format!("{} at {}", error.message, error.path))
+            .collect();
         return Ok(SaveResult {
             success: false,
-            message: "Configuration file does not exist".to_string(),
+            message: format!("Configuration validation failed: {}", details.join("; ")),
+            path: None,
+        });
+    }
+    if let Err(validation_error) = validate_claude_config_structure(&imported) {
+        return Ok(SaveResult {
+            success: false,
+            message: format!("Configuration validation failed: {}", validation_error),
+            path: None,
         });
     }
 
-    // Create timestamped backup
-    let timestamp = std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .unwrap_or_default()
-        .as_secs();
-
-    let manual_backup_path = format!("{}.manual_backup_{}", config_path, timestamp);
-
-    fs::copy(&config_path, &manual_backup_path)
-        .map_err(|e| format!("Failed to create manual backup: {}", e))?;
+    let _write_guard = state.lock_config_writes().await;
+    let (previous, config_path) = state.load_or_create_config(None, true).await?;
+    state
+        .save_config(&config_path, &imported, "Import config")
+        .await?;
+    state
+        .emit_event(
+            &app_handle,
+            "config-changed",
+            ConfigChange::between(&previous, &imported).payload(),
+        )
+        .await;
 
     Ok(SaveResult {
         success: true,
-        message: format!("Manual backup created: {}", manual_backup_path),
+        message: format!(
+            "Imported {} server(s)",
+            imported.mcp_servers.len() + imported.disabled_servers.len()
+        ),
+        path: Some(config_path.clone()),
     })
 }
 
 #[tauri::command]
-fn get_settings_path() -> Result<String, String> {
-    #[cfg(target_os = "windows")]
-    {
-        let appdata =
-            env::var("APPDATA").map_err(|_| "Could not determine APPDATA directory".to_string())?;
-        Ok(format!("{}\\mcp-manager\\settings.json", appdata))
-    }
+async fn export_servers(
+    state: tauri::State<'_, AppState>,
+    names: Vec<String>,
+    strip_secrets: bool,
+    output_path: Option<String>,
+) -> Result<String, String> {
+    let servers = internal_parse_claude_json(&state, None).await?;
+
+    let mut exported = IndexMap::new();
+    for name in &names {
+        let server_info = servers
+            .iter()
+            .find(|server| &server.name == name)
+            .ok_or_else(|| format!("Server '{}' not found", name))?;
+
+        // Keep env keys but blank out the values when sharing
+        let env: HashMap<String, String> = if strip_secrets {
+            mcp_server::McpServerInfoSanitized::from_server_info(server_info, true)
+                .env_keys
+                .into_iter()
+                .map(|key| (key, String::new()))
+                .collect()
+        } else {
+            server_info.env.clone()
+        };
 
-    #[cfg(target_os = "macos")]
-    {
-        let home_dir =
-            env::var("HOME").map_err(|_| "Could not determine home directory".to_string())?;
-        Ok(format!(
-            "{}/Library/Application Support/mcp-manager/settings.json",
-            home_dir
-        ))
+        exported.insert(
+            name.clone(),
+            McpServer {
+                command: server_info.command.clone(),
+                args: server_info.args.clone(),
+                env: if env.is_empty() { None } else { Some(env) },
+                cwd: server_info.cwd.clone(),
+            },
+        );
     }
 
-    #[cfg(target_os = "linux")]
-    {
-        let home_dir =
-            env::var("HOME").map_err(|_| "Could not determine home directory".to_string())?;
-        Ok(format!("{}/.config/mcp-manager/settings.json", home_dir))
-    }
+    let export_json = serde_json::to_string_pretty(&ClaudeConfig {
+        mcp_servers: exported,
+        disabled_servers: IndexMap::new(),
+    })
+    .map_err(|e| format!("Failed to serialize export: {}", e))?;
 
-    #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
-    {
-        Err("Unsupported operating system".to_string())
+    if let Some(path) = output_path.filter(|path| !path.trim().is_empty()) {
+        fs::write(&path, &export_json).map_err(|e| format!("Failed to write export: {}", e))?;
     }
+
+    Ok(export_json)
 }
 
-// MCP Server Status
-#[derive(Debug, Serialize, Clone)]
-pub struct McpServerStatus {
-    pub running: bool,
-    pub port: Option<u16>,
-    pub sse_path: Option<String>,
-    pub url: Option<String>,
+#[tauri::command]
+async fn enable_server(
+    state: tauri::State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+    name: String,
+) -> Result<SaveResult, String> {
+    internal_set_server_enabled(&state, name, true, Some(&app_handle)).await
 }
 
-// Shared state for real-time sync between GUI and MCP server
-#[derive(Debug, Clone)]
-pub struct AppState {
-    pub config_cache: Arc<RwLock<Option<ClaudeConfig>>>,
-    pub settings_cache: Arc<RwLock<AppSettings>>,
-    pub config_path: Arc<RwLock<String>>,
-    pub mcp_server_status: Arc<RwLock<McpServerStatus>>,
-    pub mcp_server_cancellation: Arc<RwLock<Option<CancellationToken>>>,
+#[tauri::command]
+async fn disable_server(
+    state: tauri::State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+    name: String,
+) -> Result<SaveResult, String> {
+    internal_set_server_enabled(&state, name, false, Some(&app_handle)).await
 }
 
-impl AppState {
-    pub fn new() -> Self {
-        Self {
-            config_cache: Arc::new(RwLock::new(None)),
-            settings_cache: Arc::new(RwLock::new(AppSettings::default())),
-            config_path: Arc::new(RwLock::new(String::new())),
-            mcp_server_status: Arc::new(RwLock::new(McpServerStatus {
-                running: false,
-                port: None,
-                sse_path: None,
-                url: None,
-            })),
-            mcp_server_cancellation: Arc::new(RwLock::new(None)),
+#[tauri::command]
+fn get_default_config_path() -> Result<String, String> {
+    get_claude_config_path()
+}
+
+#[tauri::command]
+async fn list_config_targets(
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<ConfigTarget>, String> {
+    let configured = {
+        let settings_guard = state.settings_cache.read().await;
+        settings_guard.config_targets.clone()
+    };
+
+    let mut targets = configured.clone();
+
+    // Add detected defaults for known clients that haven't been configured explicitly
+    for client in KNOWN_CLIENTS {
+        if configured.iter().any(|target| target.name == *client) {
+            continue;
+        }
+        if let Ok(path) = resolve_config_path_for_client(client) {
+            targets.push(ConfigTarget {
+                name: client.to_string(),
+                path,
+            });
         }
     }
 
-    pub async fn load_config(&self, custom_path: Option<String>) -> Result<ClaudeConfig, String> {
-        let config_path = resolve_config_path(custom_path)?;
-        *self.config_path.write().await = config_path.clone();
+    Ok(targets)
+}
 
-        let file_content = fs::read_to_string(&config_path).map_err(|e| {
+#[tauri::command]
+async fn load_app_settings(state: tauri::State<'_, AppState>) -> Result<AppSettings, AppError> {
+    let settings = read_settings_file()?;
+
+    // Update the settings cache
+    {
+        let mut settings_cache = state.settings_cache.write().await;
+        *settings_cache = settings.clone();
+    }
+
+    Ok(settings)
+}
+
+fn read_settings_file() -> Result<AppSettings, AppError> {
+    let settings_path = get_settings_path()?;
+
+    if !Path::new(&settings_path).exists() {
+        // Return default settings if file doesn't exist
+        Ok(AppSettings::default())
+    } else {
+        let file_content = fs::read_to_string(&settings_path).map_err(|e| AppError::Io {
+            path: Some(settings_path.clone()),
+            message: format!("Failed to read settings file: {}", e),
+        })?;
+
+        serde_json::from_str(&file_content).map_err(|e| AppError::Parse {
+            message: format!("Failed to parse settings: {}", e),
+            details: None,
+        })
+    }
+}
+
+// Settings the running MCP server only reads when it starts
+const MCP_RESTART_SETTINGS: &[&str] = &[
+    "mcpServerEnabled",
+    "mcpServerPort",
+    "mcpSsePath",
+    "mcpPostPath",
+    "mcpBindAddress",
+    "mcpAuthToken",
+    "mcpSseKeepAliveSecs",
+    "mcpTransport",
+];
+
+#[derive(Debug, Serialize)]
+struct ReloadSettingsResult {
+    // Settings keys whose values differ from the cached settings
+    changed: Vec<String>,
+    restarted: bool,
+    message: String,
+}
+
+// Re-read settings from disk and restart (or stop) a running MCP server when a
+// setting it depends on changed
+#[tauri::command]
+async fn reload_settings(
+    state: tauri::State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+) -> Result<ReloadSettingsResult, AppError> {
+    let settings = read_settings_file()?;
+    let previous = {
+        let mut settings_cache = state.settings_cache.write().await;
+        std::mem::replace(&mut *settings_cache, settings.clone())
+    };
+
+    let to_map = |settings: &AppSettings| match serde_json::to_value(settings) {
+        Ok(serde_json::Value::Object(map)) => map,
+        _ => serde_json::Map::new(),
+    };
+    let (previous_map, current_map) = (to_map(&previous), to_map(&settings));
+    let changed: Vec<String> = current_map
+        .iter()
+        .filter(|(key, value)| previous_map.get(*key) != Some(*value))
+        .map(|(key, _)| key.clone())
+        .collect();
+
+    if previous.claude_config_path != settings.claude_config_path {
+        let config_path = resolve_config_path(Some(settings.claude_config_path.clone()))?;
+        if let Err(e) =
+            config_watcher::start_config_watcher(app_handle, state.inner().clone(), config_path)
+                .await
+        {
+            tracing::error!(error = %e, "Failed to watch config file");
+        }
+    }
+
+    // The log filter can be swapped without a restart
+    if changed.iter().any(|key| key == "mcpLogLevel") {
+        mcp_server::init_tracing(&settings.mcp_log_level);
+    }
+
+    let running = state.mcp_server_status.read().await.running;
+    let needs_restart = running
+        && changed
+            .iter()
+            .any(|key| MCP_RESTART_SETTINGS.contains(&key.as_str()));
+
+    if !needs_restart {
+        return Ok(ReloadSettingsResult {
+            message: format!("Reloaded settings, {} changed", changed.len()),
+            changed,
+            restarted: false,
+        });
+    }
+
+    internal_stop_mcp_server(state.inner()).await?;
+    if !settings.mcp_server_enabled {
+        return Ok(ReloadSettingsResult {
+            message: "Reloaded settings and stopped the disabled MCP server".to_string(),
+            changed,
+            restarted: false,
+        });
+    }
+
+    let result = internal_start_mcp_server(state.inner()).await?;
+    Ok(ReloadSettingsResult {
+        message: if result.success {
             format!(
-                "Failed to read Claude Desktop config at {}: {}",
-                config_path, e
+                "Reloaded settings and {}",
+                mcp_server_start_message("restarted", &settings)
             )
-        })?;
+        } else {
+            format!(
+                "Reloaded settings but the MCP server failed to restart: {}",
+                result.message
+            )
+        },
+        changed,
+        restarted: result.success,
+    })
+}
 
-        let config: ClaudeConfig = match serde_json::from_str(&file_content) {
-            Ok(config) => config,
-            Err(e) => {
-                let mut error_info = analyze_json_error(&file_content, &e);
-                let backup_path = format!("{}.backup", config_path);
-                error_info.has_backup = Path::new(&backup_path).exists();
-                let error_json = serde_json::to_string(&error_info).unwrap_or_else(|_| {
-                    format!(
-                        "{{\"error_type\":\"unknown\",\"message\":\"Failed to parse JSON: {}\"}}",
-                        e
-                    )
-                });
-                return Err(format!("JSON_ERROR:{}", error_json));
+#[tauri::command]
+async fn save_app_settings(
+    state: tauri::State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+    settings: AppSettings,
+) -> Result<SaveResult, String> {
+    if let Err(message) = parse_bind_address(&settings.mcp_bind_address) {
+        return Ok(SaveResult {
+            success: false,
+            message,
+            path: None,
+        });
+    }
+
+    if let Err(message) = check_sse_path(&settings.mcp_sse_path, &settings.mcp_post_path) {
+        return Ok(SaveResult {
+            success: false,
+            message,
+            path: None,
+        });
+    }
+
+    if let Err(message) = check_post_path(&settings.mcp_post_path, &settings.mcp_sse_path) {
+        return Ok(SaveResult {
+            success: false,
+            message,
+            path: None,
+        });
+    }
+
+    if let Err(message) = check_sse_keep_alive(settings.mcp_sse_keep_alive_secs) {
+        return Ok(SaveResult {
+            success: false,
+            message,
+            path: None,
+        });
+    }
+
+    if let Err(message) = check_backup_retention(settings.backup_retention_count) {
+        return Ok(SaveResult {
+            success: false,
+            message,
+            path: None,
+        });
+    }
+
+    if let Err(e) = tracing_subscriber::EnvFilter::try_new(&settings.mcp_log_level) {
+        return Ok(SaveResult {
+            success: false,
+            message: format!("Invalid log level '{}': {}", settings.mcp_log_level, e),
+            path: None,
+        });
+    }
+
+    write_settings_file(&settings)?;
+
+    // Update the settings cache
+    let previous_config_path = {
+        let mut settings_cache = state.settings_cache.write().await;
+        let previous = settings_cache.claude_config_path.clone();
+        *settings_cache = settings.clone();
+        previous
+    };
+
+    // Re-arm the config watcher if the config path changed
+    if previous_config_path != settings.claude_config_path {
+        let config_path = resolve_config_path(Some(settings.claude_config_path.clone()))?;
+        if let Err(e) =
+            config_watcher::start_config_watcher(app_handle, state.inner().clone(), config_path)
+                .await
+        {
+            tracing::error!(error = %e, "Failed to watch config file");
+        }
+    }
+
+    Ok(SaveResult {
+        success: true,
+        message: "Settings saved successfully".to_string(),
+        path: None,
+    })
+}
+
+// Change only the theme, re-reading the rest of the settings from disk so values
+// saved elsewhere since this window loaded them aren't overwritten
+#[tauri::command]
+async fn set_dark_mode(
+    state: tauri::State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+    enabled: bool,
+) -> Result<SaveResult, AppError> {
+    {
+        let mut settings_cache = state.settings_cache.write().await;
+        let mut settings = read_settings_file()?;
+        settings.dark_mode = enabled;
+        write_settings_file(&settings)?;
+        settings_cache.dark_mode = enabled;
+    }
+
+    state
+        .emit_event(
+            &app_handle,
+            "theme-changed",
+            serde_json::json!({ "darkMode": enabled }),
+        )
+        .await;
+
+    Ok(SaveResult {
+        success: true,
+        message: format!("Dark mode {}", if enabled { "enabled" } else { "disabled" }),
+        path: None,
+    })
+}
+
+fn write_settings_file(settings: &AppSettings) -> Result<(), String> {
+    let settings_path = get_settings_path()?;
+    let settings_dir = Path::new(&settings_path)
+        .parent()
+        .ok_or("Could not determine settings directory")?;
+
+    // Create settings directory if it doesn't exist
+    if !settings_dir.exists() {
+        fs::create_dir_all(settings_dir)
+            .map_err(|e| format!("Failed to create settings directory: {}", e))?;
+    }
+
+    let settings_json = serde_json::to_string_pretty(settings)
+        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+
+    fs::write(&settings_path, settings_json)
+        .map_err(|e| format!("Failed to write settings file: {}", e))
+}
+
+#[tauri::command]
+fn get_preset_servers() -> Vec<PresetServer> {
+    get_preset_servers_database()
+}
+
+#[tauri::command]
+fn get_preset_servers_by_category(category: String) -> Vec<PresetServer> {
+    get_preset_servers_database()
+        .into_iter()
+        .filter(|server| server.category == category)
+        .collect()
+}
+
+#[tauri::command]
+fn get_preset_server_categories() -> Vec<String> {
+    let mut categories: Vec<String> = get_preset_servers_database()
+        .into_iter()
+        .map(|server| server.category)
+        .collect();
+
+    // Remove duplicates and sort
+    categories.sort();
+    categories.dedup();
+    categories
+}
+
+#[tauri::command]
+fn add_custom_preset(preset: PresetServer) -> Result<SaveResult, String> {
+    if preset.name.trim().is_empty() {
+        return Ok(SaveResult {
+            success: false,
+            message: "Preset name cannot be empty".to_string(),
+            path: None,
+        });
+    }
+
+    if !preset.validate_command_matches_type() {
+        return Ok(SaveResult {
+            success: false,
+            message: format!(
+                "Command '{}' does not match server type '{}'",
+                preset.command, preset.server_type
+            ),
+            path: None,
+        });
+    }
+
+    let mut presets = load_custom_presets()?;
+    let name = preset.name.clone();
+    let replaced = match presets.iter_mut().find(|existing| existing.name == name) {
+        Some(existing) => {
+            *existing = preset;
+            true
+        }
+        None => {
+            presets.push(preset);
+            false
+        }
+    };
+    save_custom_presets(&presets)?;
+
+    Ok(SaveResult {
+        success: true,
+        message: format!(
+            "Custom preset '{}' {}",
+            name,
+            if replaced { "updated" } else { "added" }
+        ),
+        path: None,
+    })
+}
+
+#[tauri::command]
+fn delete_custom_preset(name: String) -> Result<SaveResult, String> {
+    let mut presets = load_custom_presets()?;
+    let before = presets.len();
+    presets.retain(|preset| preset.name != name);
+
+    if presets.len() == before {
+        return Ok(SaveResult {
+            success: false,
+            message: format!("Custom preset '{}' not found", name),
+            path: None,
+        });
+    }
+
+    save_custom_presets(&presets)?;
+    Ok(SaveResult {
+        success: true,
+        message: format!("Custom preset '{}' deleted", name),
+        path: None,
+    })
+}
+
+#[tauri::command]
+fn get_preset_server_by_name(name: String) -> Option<PresetServer> {
+    get_preset_servers_database()
+        .into_iter()
+        .find(|server| server.name == name)
+}
+
+#[tauri::command]
+fn get_preset_servers_by_type(server_type: String) -> Vec<PresetServer> {
+    let target_type = match server_type.to_lowercase().as_str() {
+        "docker" => ServerType::Docker,
+        "npx" => ServerType::Npx,
+        "uvx" => ServerType::Uvx,
+        "uv" => ServerType::Uv,
+        _ => ServerType::Other(server_type),
+    };
+
+    get_preset_servers_database()
+        .into_iter()
+        .filter(|server| server.server_type == target_type)
+        .collect()
+}
+
+// The command and arg skeleton for a new server of `server_type`, with `target` (a
+// docker image, or a package for npx/uvx/uv) filled in. Any other type is taken as
+// the command itself with `target` as its only argument.
+#[tauri::command]
+fn scaffold_server(server_type: String, target: String) -> Result<McpServerEdit, String> {
+    let target = target.trim().to_string();
+    if target.is_empty() {
+        return Err("An image or package name is required".to_string());
+    }
+
+    let (command, args) = match ServerType::from_command(server_type.trim()) {
+        ServerType::Docker => (
+            "docker".to_string(),
+            vec![
+                "run".to_string(),
+                "-i".to_string(),
+                "--rm".to_string(),
+                target,
+            ],
+        ),
+        ServerType::Npx => ("npx".to_string(), vec!["-y".to_string(), target]),
+        ServerType::Uvx => ("uvx".to_string(), vec![target]),
+        ServerType::Uv => ("uv".to_string(), vec!["run".to_string(), target]),
+        ServerType::Other(command) if !command.is_empty() => (command, vec![target]),
+        ServerType::Other(_) => return Err("Server type cannot be empty".to_string()),
+    };
+
+    Ok(McpServerEdit {
+        command,
+        args,
+        env: HashMap::new(),
+        cwd: None,
+    })
+}
+
+// Full preset definitions, default env and legacy API key fields included, matching
+// every filter that is given. Unlike the MCP tools nothing is sanitized.
+#[tauri::command]
+fn get_full_presets_filtered(
+    category: Option<String>,
+    server_type: Option<String>,
+    requires_api_key: Option<bool>,
+) -> Vec<PresetServer> {
+    let server_type = server_type.map(|server_type| ServerType::from_command(&server_type));
+
+    get_preset_servers_database()
+        .into_iter()
+        .filter(|preset| {
+            category
+                .as_ref()
+                .is_none_or(|category| preset.category == *category)
+        })
+        .filter(|preset| {
+            server_type
+                .as_ref()
+                .is_none_or(|server_type| preset.server_type == *server_type)
+        })
+        .filter(|preset| requires_api_key.is_none_or(|requires| preset.needs_api_key() == requires))
+        .collect()
+}
+
+#[tauri::command]
+fn get_preset_servers_by_api_requirement(requires: bool) -> Vec<PresetServer> {
+    get_preset_servers_database()
+        .into_iter()
+        .filter(|server| server.needs_api_key() == requires)
+        .collect()
+}
+
+#[tauri::command]
+async fn compare_with_preset(
+    state: tauri::State<'_, AppState>,
+    name: String,
+    custom_path: Option<String>,
+) -> Result<PresetComparison, String> {
+    internal_compare_with_preset(&state, name, custom_path).await
+}
+
+#[tauri::command]
+async fn get_preset_install_status(
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<PresetInstallStatus>, String> {
+    internal_get_preset_install_status(&state).await
+}
+
+#[tauri::command]
+fn get_server_types() -> Vec<String> {
+    let mut types: Vec<String> = get_preset_servers_database()
+        .into_iter()
+        .map(|server| server.server_type.to_string())
+        .collect();
+
+    // Remove duplicates and sort
+    types.sort();
+    types.dedup();
+    types
+}
+
+#[tauri::command]
+async fn search_servers(
+    state: tauri::State<'_, AppState>,
+    query: String,
+    include_installed: Option<bool>,
+) -> Result<Vec<SearchResult>, String> {
+    let installed_names: Vec<String> = if include_installed.unwrap_or(false) {
+        internal_parse_claude_json(&state, None)
+            .await?
+            .into_iter()
+            .map(|server| server.name)
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    Ok(rank_search_results(&query, &installed_names))
+}
+
+#[tauri::command]
+async fn get_server_metadata(
+    state: tauri::State<'_, AppState>,
+    custom_path: Option<String>,
+) -> Result<HashMap<String, ServerMetadata>, String> {
+    let config_path = resolve_config_path(custom_path.clone())?;
+    let servers = internal_parse_claude_json(&state, custom_path).await?;
+
+    let mut metadata = load_server_metadata(&config_path);
+
+    // Backfill servers that were added before tracking existed (or outside the app)
+    let now = unix_now();
+    let mut backfilled = false;
+    for server in &servers {
+        if !metadata.contains_key(&server.name) {
+            metadata.insert(
+                server.name.clone(),
+                ServerMetadata {
+                    added_at: now,
+                    updated_at: now,
+                },
+            );
+            backfilled = true;
+        }
+    }
+
+    // Drop entries for servers that no longer exist
+    let before = metadata.len();
+    metadata.retain(|name, _| servers.iter().any(|server| &server.name == name));
+    if backfilled || metadata.len() != before {
+        if let Err(e) = save_server_metadata(&config_path, &metadata) {
+            tracing::warn!("{}", e);
+        }
+    }
+
+    Ok(metadata)
+}
+
+#[tauri::command]
+fn validate_config_schema(
+    custom_path: Option<String>,
+) -> Result<Vec<SchemaValidationError>, String> {
+    let config_path = resolve_config_path(custom_path)?;
+    let file_content = fs::read_to_string(&config_path)
+        .map_err(|e| ConfigIoError::from_io_error(&config_path, &e).to_error_string())?;
+
+    let raw_config: serde_json::Value =
+        serde_json::from_str(normalize_config_content(&file_content))
+            .map_err(|e| format!("Failed to parse JSON: {}", e))?;
+
+    validate_against_schema(&raw_config)
+}
+
+#[tauri::command]
+async fn validate_server_config(
+    state: tauri::State<'_, AppState>,
+    server: PresetServer,
+    check_command: Option<bool>,
+    inspect_docker_image: Option<bool>,
+) -> ServerValidation {
+    let valid = server.validate_command_matches_type()
+        && (!check_command.unwrap_or(false)
+            || state
+                .resolve_command_cached(&server.command)
+                .await
+                .is_some());
+
+    let warnings = if ServerType::from_command(&server.command) == ServerType::Docker {
+        validate_docker_args(&server.args, inspect_docker_image.unwrap_or(false)).await
+    } else {
+        Vec::new()
+    };
+
+    ServerValidation { valid, warnings }
+}
+
+// `docker run` options that take the next arg as their value
+const DOCKER_RUN_VALUE_OPTIONS: &[&str] = &[
+    "-e",
+    "--env",
+    "--env-file",
+    "-v",
+    "--volume",
+    "--mount",
+    "-p",
+    "--publish",
+    "--name",
+    "--network",
+    "-w",
+    "--workdir",
+    "-u",
+    "--user",
+    "--entrypoint",
+    "--platform",
+    "-l",
+    "--label",
+    "--add-host",
+    "-m",
+    "--memory",
+    "--cpus",
+    "-h",
+    "--hostname",
+];
+
+// The image a `docker run ...` argument list would start
+fn docker_run_image(args: &[String]) -> Result<&str, String> {
+    let Some(run_index) = args.iter().position(|arg| arg == "run") else {
+        return Err("Docker args do not include 'run'".to_string());
+    };
+
+    let mut remaining = args[run_index + 1..].iter();
+    while let Some(arg) = remaining.next() {
+        if DOCKER_RUN_VALUE_OPTIONS.contains(&arg.as_str()) {
+            remaining.next();
+        } else if !arg.starts_with('-') {
+            return Ok(arg.as_str());
+        }
+    }
+
+    Err("Docker args do not include an image to run".to_string())
+}
+
+// Warnings for docker server args: a missing `run`, a missing or malformed image and,
+// when `inspect_image` is set, an image that hasn't been pulled locally
+async fn validate_docker_args(args: &[String], inspect_image: bool) -> Vec<String> {
+    let image = match docker_run_image(args) {
+        Ok(image) => image,
+        Err(warning) => return vec![warning],
+    };
+
+    if !is_plausible_image_reference(image) {
+        return vec![format!(
+            "'{}' does not look like a valid docker image reference",
+            image
+        )];
+    }
+
+    if inspect_image {
+        let inspected = tokio::process::Command::new("docker")
+            .args(["image", "inspect", image])
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .status()
+            .await;
+        match inspected {
+            Ok(status) if status.success() => {}
+            Ok(_) => {
+                return vec![format!(
+                    "Docker image '{}' is not available locally; run 'docker pull {}'",
+                    image, image
+                )]
+            }
+            Err(e) => return vec![format!("Failed to run docker image inspect: {}", e)],
+        }
+    }
+
+    Vec::new()
+}
+
+// `[registry[:port]/]path[:tag][@sha256:digest]` with lowercase path components
+fn is_plausible_image_reference(image: &str) -> bool {
+    let (name_and_tag, digest) = match image.split_once('@') {
+        Some((name_and_tag, digest)) => (name_and_tag, Some(digest)),
+        None => (image, None),
+    };
+
+    if let Some(digest) = digest {
+        let valid_digest = digest
+            .strip_prefix("sha256:")
+            .is_some_and(|hex| hex.len() == 64 && hex.chars().all(|c| c.is_ascii_hexdigit()));
+        if !valid_digest {
+            return false;
+        }
+    }
+
+    // A ':' after the last '/' starts the tag; earlier ones belong to a registry port
+    let last_slash = name_and_tag.rfind('/').map_or(0, |index| index + 1);
+    let (name, tag) = match name_and_tag[last_slash..].find(':') {
+        Some(index) => (
+            &name_and_tag[..last_slash + index],
+            Some(&name_and_tag[last_slash + index + 1..]),
+        ),
+        None => (name_and_tag, None),
+    };
+
+    if let Some(tag) = tag {
+        let valid_tag = !tag.is_empty()
+            && tag.len() <= 128
+            && !tag.starts_with(['.', '-'])
+            && tag
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-'));
+        if !valid_tag {
+            return false;
+        }
+    }
+
+    let mut components: Vec<&str> = name.split('/').collect();
+    // The first component is a registry host when it looks like one
+    if components.len() > 1 && (components[0].contains(['.', ':']) || components[0] == "localhost")
+    {
+        components.remove(0);
+    }
+
+    !components.is_empty()
+        && components.iter().all(|component| {
+            !component.is_empty()
+                && component.starts_with(|c: char| c.is_ascii_lowercase() || c.is_ascii_digit())
+                && component.ends_with(|c: char| c.is_ascii_lowercase() || c.is_ascii_digit())
+                && component.chars().all(|c| {
+                    c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '.' | '_' | '-')
+                })
+        })
+}
+
+#[tauri::command]
+async fn check_command_availability(
+    state: tauri::State<'_, AppState>,
+    command: String,
+) -> Result<CommandAvailability, String> {
+    let resolved_path = state.resolve_command_cached(&command).await;
+
+    Ok(CommandAvailability {
+        found: resolved_path.is_some(),
+        resolved_path: resolved_path.map(|path| path.to_string_lossy().to_string()),
+    })
+}
+
+// Internal function for starting MCP server (used by both Tauri command and auto-start)
+async fn internal_start_mcp_server(state: &AppState) -> Result<SaveResult, String> {
+    let settings = {
+        let settings_guard = state.settings_cache.read().await;
+        settings_guard.clone()
+    };
+
+    mcp_server::init_tracing(&settings.mcp_log_level);
+    tracing::debug!(
+        enabled = settings.mcp_server_enabled,
+        port = settings.mcp_server_port,
+        sse_path = %settings.mcp_sse_path,
+        "Starting MCP server"
+    );
+
+    if !settings.mcp_server_enabled {
+        return Ok(SaveResult {
+            success: false,
+            message: "MCP server is disabled in settings".to_string(),
+            path: None,
+        });
+    }
+
+    // Check if server is already running
+    {
+        let status_guard = state.mcp_server_status.read().await;
+        if status_guard.running {
+            return Ok(SaveResult {
+                success: false,
+                message: "MCP server is already running".to_string(),
+                path: None,
+            });
+        }
+    }
+
+    // stdio binds no socket, so none of the HTTP checks apply
+    let bind_address = match settings.mcp_transport {
+        McpTransport::Sse => match check_sse_transport(&settings).await {
+            Ok(bind_address) => Some(bind_address),
+            Err(message) => {
+                return Ok(SaveResult {
+                    success: false,
+                    message,
+                    path: None,
+                });
+            }
+        },
+        McpTransport::Stdio => None,
+    };
+
+    // Create cancellation token
+    let cancellation_token = CancellationToken::new();
+
+    // Store cancellation token
+    {
+        let mut token_guard = state.mcp_server_cancellation.write().await;
+        *token_guard = Some(cancellation_token.clone());
+    }
+
+    // Update server status
+    {
+        let mut status_guard = state.mcp_server_status.write().await;
+        status_guard.running = true;
+        status_guard.transport = Some(settings.mcp_transport);
+        if let Some(bind_address) = bind_address {
+            status_guard.port = Some(settings.mcp_server_port);
+            status_guard.sse_path = Some(settings.mcp_sse_path.clone());
+            status_guard.post_path = Some(settings.mcp_post_path.clone());
+            status_guard.url = Some(format!("http://{}{}", bind_address, settings.mcp_sse_path));
+            status_guard.auth_header = settings
+                .auth_token()
+                .map(|_| "Authorization: Bearer <mcpAuthToken>".to_string());
+            status_guard.bind_address = Some(bind_address.to_string());
+        }
+        status_guard.pid = Some(std::process::id());
+        status_guard.started_at = Some(unix_now());
+        status_guard.last_error = None;
+    }
+
+    // Start MCP server in background
+    let state_clone = state.clone();
+    let server_task = tokio::spawn(async move {
+        if let Err(e) = mcp_server::start_mcp_server(state_clone.clone()).await {
+            tracing::error!(error = %e, "MCP server error");
+            // Reset status on error, keeping the reason for the GUI
+            let mut status_guard = state_clone.mcp_server_status.write().await;
+            *status_guard = McpServerStatus::stopped();
+            status_guard.last_error = Some(e.to_string());
+        }
+    });
+
+    // Keep the task handle so a restart can wait for the listener to be released
+    {
+        let mut task_guard = state.mcp_server_task.write().await;
+        *task_guard = Some(server_task);
+    }
+
+    Ok(SaveResult {
+        success: true,
+        message: mcp_server_start_message("started", &settings),
+        path: None,
+    })
+}
+
+// "MCP server <verb> on port N", or "on stdio" for the stdio transport
+fn mcp_server_start_message(verb: &str, settings: &AppSettings) -> String {
+    match settings.mcp_transport {
+        McpTransport::Sse => format!("MCP server {} on port {}", verb, settings.mcp_server_port),
+        McpTransport::Stdio => format!("MCP server {} on stdio", verb),
+    }
+}
+
+// Validate the SSE-specific settings and return the address to bind
+async fn check_sse_transport(settings: &AppSettings) -> Result<std::net::SocketAddr, String> {
+    check_sse_path(&settings.mcp_sse_path, &settings.mcp_post_path)?;
+    check_post_path(&settings.mcp_post_path, &settings.mcp_sse_path)?;
+    check_sse_keep_alive(settings.mcp_sse_keep_alive_secs)?;
+
+    let bind_ip = parse_bind_address(&settings.mcp_bind_address)?;
+    let bind_address = std::net::SocketAddr::new(bind_ip, settings.mcp_server_port);
+
+    // Validate port availability (basic check)
+    check_port_available(bind_address)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(bind_address)
+}
+
+#[tauri::command]
+async fn start_mcp_server(state: tauri::State<'_, AppState>) -> Result<SaveResult, String> {
+    internal_start_mcp_server(state.inner()).await
+}
+
+#[tauri::command]
+async fn stop_mcp_server(state: tauri::State<'_, AppState>) -> Result<SaveResult, String> {
+    internal_stop_mcp_server(state.inner()).await
+}
+
+#[tauri::command]
+async fn restart_mcp_server(state: tauri::State<'_, AppState>) -> Result<SaveResult, String> {
+    internal_stop_mcp_server(state.inner()).await?;
+
+    let result = internal_start_mcp_server(state.inner()).await?;
+    if !result.success {
+        return Ok(SaveResult {
+            success: false,
+            message: format!(
+                "MCP server stopped but failed to restart: {}",
+                result.message
+            ),
+            path: None,
+        });
+    }
+
+    let settings = state.settings_cache.read().await;
+    Ok(SaveResult {
+        success: true,
+        message: mcp_server_start_message("restarted", &settings),
+        path: None,
+    })
+}
+
+const MCP_SERVER_STOP_TIMEOUT_SECS: u64 = 5;
+
+// Cancel the running server and wait for its task to finish so the port is free again
+async fn internal_stop_mcp_server(state: &AppState) -> Result<SaveResult, String> {
+    // Let a config write already in progress (e.g. from an MCP tool) finish before the
+    // server is cancelled; tool calls arriving meanwhile wait and are then dropped
+    let _write_guard = state.lock_config_writes().await;
+
+    // Get and cancel the token
+    let token = {
+        let mut token_guard = state.mcp_server_cancellation.write().await;
+        token_guard.take()
+    };
+
+    if let Some(token) = token {
+        token.cancel();
+    }
+
+    let server_task = {
+        let mut task_guard = state.mcp_server_task.write().await;
+        task_guard.take()
+    };
+
+    // Open SSE streams can hold up graceful shutdown, so don't wait forever
+    if let Some(server_task) = server_task {
+        match tokio::time::timeout(
+            std::time::Duration::from_secs(MCP_SERVER_STOP_TIMEOUT_SECS),
+            server_task,
+        )
+        .await
+        {
+            Ok(Err(e)) => tracing::error!(error = %e, "MCP server task ended abnormally"),
+            Err(_) => tracing::warn!("Timed out waiting for MCP server task to stop"),
+            Ok(Ok(())) => {}
+        }
+    }
+
+    // Update server status
+    {
+        let mut status_guard = state.mcp_server_status.write().await;
+        *status_guard = McpServerStatus::stopped();
+    }
+
+    Ok(SaveResult {
+        success: true,
+        message: "MCP server stopped".to_string(),
+        path: None,
+    })
+}
+
+#[tauri::command]
+fn list_mcp_tools() -> Vec<mcp_server::McpToolSummary> {
+    mcp_server::McpManagerServer::tool_summaries()
+}
+
+// Input schemas of the MCP tools, so a client can validate arguments before calling
+#[tauri::command]
+fn get_mcp_tool_schemas() -> Vec<rmcp::model::Tool> {
+    mcp_server::McpManagerServer::tool_schemas()
+}
+
+#[tauri::command]
+async fn get_mcp_server_status(
+    state: tauri::State<'_, AppState>,
+) -> Result<McpServerStatus, String> {
+    Ok(internal_get_mcp_server_status(&state).await)
+}
+
+async fn internal_get_mcp_server_status(state: &AppState) -> McpServerStatus {
+    let mut status = state.mcp_server_status.read().await.clone();
+    status.uptime_seconds = status
+        .started_at
+        .map(|started_at| unix_now().saturating_sub(started_at));
+    status
+}
+
+// Stop the MCP server after a delay, so a client asking over MCP gets its response
+// before the connection goes away
+fn schedule_mcp_server_stop(state: &AppState, delay: std::time::Duration) {
+    let state = state.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(delay).await;
+        if let Err(e) = internal_stop_mcp_server(&state).await {
+            tracing::error!(error = %e, "Scheduled MCP server stop failed");
+        }
+    });
+}
+
+fn check_sse_path(path: &str, post_path: &str) -> Result<(), String> {
+    if !path.starts_with('/') {
+        return Err(format!("SSE path '{}' must start with '/'", path));
+    }
+
+    if path.chars().any(char::is_whitespace) {
+        return Err(format!("SSE path '{}' must not contain whitespace", path));
+    }
+
+    if path == post_path {
+        return Err(format!(
+            "SSE path cannot be '{}' because it is used for client messages",
+            post_path
+        ));
+    }
+
+    Ok(())
+}
+
+fn check_post_path(path: &str, sse_path: &str) -> Result<(), String> {
+    if !path.starts_with('/') {
+        return Err(format!("Message path '{}' must start with '/'", path));
+    }
+
+    if path.chars().any(char::is_whitespace) {
+        return Err(format!(
+            "Message path '{}' must not contain whitespace",
+            path
+        ));
+    }
+
+    if path == sse_path {
+        return Err(format!(
+            "Message path cannot be '{}' because it is the SSE path",
+            sse_path
+        ));
+    }
+
+    Ok(())
+}
+
+// Keep-alive pings must be frequent enough to beat proxy idle timeouts but not spam the client
+const MIN_SSE_KEEP_ALIVE_SECS: u64 = 5;
+const MAX_SSE_KEEP_ALIVE_SECS: u64 = 300;
+
+fn check_sse_keep_alive(secs: Option<u64>) -> Result<(), String> {
+    match secs {
+        Some(secs) if !(MIN_SSE_KEEP_ALIVE_SECS..=MAX_SSE_KEEP_ALIVE_SECS).contains(&secs) => {
+            Err(format!(
+                "SSE keep-alive must be between {} and {} seconds",
+                MIN_SSE_KEEP_ALIVE_SECS, MAX_SSE_KEEP_ALIVE_SECS
+            ))
+        }
+        _ => Ok(()),
+    }
+}
+
+#[tauri::command]
+fn validate_sse_path(path: String, post_path: Option<String>) -> SaveResult {
+    let post_path = post_path.unwrap_or_else(default_mcp_post_path);
+    match check_sse_path(&path, &post_path) {
+        Ok(()) => SaveResult {
+            success: true,
+            message: format!("SSE path '{}' is valid", path),
+            path: None,
+        },
+        Err(message) => SaveResult {
+            success: false,
+            message,
+            path: None,
+        },
+    }
+}
+
+fn parse_bind_address(address: &str) -> Result<std::net::IpAddr, String> {
+    address
+        .trim()
+        .parse()
+        .map_err(|_| format!("'{}' is not a valid IP address to bind to", address))
+}
+
+fn random_hex_token(byte_count: usize) -> String {
+    use rand::RngCore;
+
+    let mut bytes = vec![0u8; byte_count];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+#[tauri::command]
+fn generate_mcp_token() -> String {
+    random_hex_token(32)
+}
+
+// Ports below this are privileged on most systems
+const MIN_MCP_PORT: u16 = 1024;
+
+// How long a port availability check may take before it is reported as timed out
+const PORT_CHECK_TIMEOUT_MS: u64 = 2000;
+
+// Try to bind the address, reporting why it failed in terms a user can act on
+async fn check_port_available(bind_address: std::net::SocketAddr) -> Result<(), AppError> {
+    let bind = tokio::time::timeout(
+        std::time::Duration::from_millis(PORT_CHECK_TIMEOUT_MS),
+        tokio::net::TcpListener::bind(bind_address),
+    )
+    .await;
+
+    match bind {
+        Ok(Ok(_)) => Ok(()),
+        Err(_) => Err(AppError::Other {
+            message: format!(
+                "Timed out after {}ms checking whether port {} is available on {}",
+                PORT_CHECK_TIMEOUT_MS,
+                bind_address.port(),
+                bind_address.ip()
+            ),
+        }),
+        Ok(Err(e)) => Err(match e.kind() {
+            std::io::ErrorKind::AddrInUse => AppError::PortInUse {
+                port: bind_address.port(),
+                message: format!(
+                    "Port {} is already in use on {}",
+                    bind_address.port(),
+                    bind_address.ip()
+                ),
+            },
+            std::io::ErrorKind::PermissionDenied => AppError::Io {
+                path: None,
+                message: format!(
+                    "Permission denied binding to port {} on {}",
+                    bind_address.port(),
+                    bind_address.ip()
+                ),
+            },
+            std::io::ErrorKind::AddrNotAvailable => AppError::Validation {
+                message: format!(
+                    "Address {} is not available on this machine",
+                    bind_address.ip()
+                ),
+            },
+            _ => AppError::Io {
+                path: None,
+                message: format!("Cannot bind to {}: {}", bind_address, e),
+            },
+        }),
+    }
+}
+
+// Checks the given address, or the configured bind address when none is passed
+#[tauri::command]
+async fn validate_mcp_port(
+    state: tauri::State<'_, AppState>,
+    port: u16,
+    address: Option<String>,
+) -> Result<SaveResult, String> {
+    if port < MIN_MCP_PORT {
+        return Ok(SaveResult {
+            success: false,
+            message: format!(
+                "Port {} is a privileged port; choose one between {} and 65535",
+                port, MIN_MCP_PORT
+            ),
+            path: None,
+        });
+    }
+
+    let address = match address.filter(|address| !address.trim().is_empty()) {
+        Some(address) => address,
+        None => state.settings_cache.read().await.mcp_bind_address.clone(),
+    };
+    let bind_ip = match parse_bind_address(&address) {
+        Ok(ip) => ip,
+        Err(message) => {
+            return Ok(SaveResult {
+                success: false,
+                message,
+                path: None,
+            });
+        }
+    };
+
+    // Try to bind to the port to check availability
+    match check_port_available(std::net::SocketAddr::new(bind_ip, port)).await {
+        Ok(()) => Ok(SaveResult {
+            success: true,
+            message: format!("Port {} is available on {}", port, bind_ip),
+            path: None,
+        }),
+        Err(e) => Ok(SaveResult {
+            success: false,
+            message: e.to_string(),
+            path: None,
+        }),
+    }
+}
+
+// Entries returned by get_audit_log_tail when no limit is given
+const DEFAULT_AUDIT_LOG_TAIL: usize = 50;
+
+#[tauri::command]
+async fn get_audit_log_tail(
+    state: tauri::State<'_, AppState>,
+    limit: Option<usize>,
+) -> Result<Vec<audit_log::AuditEntry>, String> {
+    let path = match state.settings_cache.read().await.audit_log_path() {
+        Some(path) => path.to_string(),
+        None => return Ok(Vec::new()),
+    };
+
+    audit_log::read_tail(&path, limit.unwrap_or(DEFAULT_AUDIT_LOG_TAIL))
+}
+
+#[tauri::command]
+fn get_backup_info(custom_path: Option<String>) -> Result<Option<BackupInfo>, String> {
+    let config_path = resolve_config_path(custom_path)?;
+    let backup_path = format!("{}.backup", config_path);
+
+    if !Path::new(&backup_path).exists() {
+        return Ok(None);
+    }
+
+    read_backup_info(backup_path).map(Some)
+}
+
+#[tauri::command]
+fn list_config_artifacts(custom_path: Option<String>) -> Result<Vec<ConfigArtifact>, String> {
+    let config_path = resolve_config_path(custom_path)?;
+    Ok(collect_config_artifacts(&config_path))
+}
+
+// Delete the listed artifacts, or every artifact whose file name matches `pattern`
+// (`*` wildcards) when no paths are given; `older_than_days` narrows either selection.
+// Only files reported by list_config_artifacts can be deleted, never the live config.
+#[tauri::command]
+fn cleanup_artifacts(
+    custom_path: Option<String>,
+    paths: Option<Vec<String>>,
+    pattern: Option<String>,
+    older_than_days: Option<u64>,
+) -> Result<ArtifactCleanupResult, String> {
+    let config_path = resolve_config_path(custom_path)?;
+    if paths.is_none() && pattern.is_none() && older_than_days.is_none() {
+        return Err("Select artifacts to delete by path, pattern or age".to_string());
+    }
+
+    let cutoff = older_than_days.map(|days| unix_now().saturating_sub(days * 24 * 60 * 60));
+    let selected: Vec<ConfigArtifact> = collect_config_artifacts(&config_path)
+        .into_iter()
+        .filter(|artifact| match &paths {
+            Some(paths) => paths.contains(&artifact.path),
+            None => pattern.as_deref().is_none_or(|pattern| {
+                let file_name = Path::new(&artifact.path)
+                    .file_name()
+                    .map(|name| name.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                wildcard_match(pattern, &file_name)
+            }),
+        })
+        .filter(|artifact| cutoff.is_none_or(|cutoff| artifact.modified < cutoff))
+        .collect();
+
+    let mut deleted = Vec::new();
+    let mut failed = Vec::new();
+    for artifact in selected {
+        match fs::remove_file(&artifact.path) {
+            Ok(()) => deleted.push(artifact.path),
+            Err(e) => failed.push(format!("{}: {}", artifact.path, e)),
+        }
+    }
+
+    let message = format!(
+        "Deleted {} artifact(s){}",
+        deleted.len(),
+        if failed.is_empty() {
+            String::new()
+        } else {
+            format!(", {} failed", failed.len())
+        }
+    );
+
+    Ok(ArtifactCleanupResult {
+        deleted,
+        failed,
+        message,
+    })
+}
+
+#[tauri::command]
+fn list_backups(custom_path: Option<String>) -> Result<Vec<BackupInfo>, String> {
+    let config_path = resolve_config_path(custom_path)?;
+
+    collect_timestamped_backups(&config_path)
+        .into_iter()
+        .map(|(path, _)| read_backup_info(path.to_string_lossy().to_string()))
+        .collect()
+}
+
+// Backups and `.broken` copies next to the config, newest first. The metadata sidecar
+// is still in use and is not an artifact.
+fn collect_config_artifacts(config_path: &str) -> Vec<ConfigArtifact> {
+    let config = Path::new(config_path);
+    let (Some(dir), Some(file_name)) = (config.parent(), config.file_name()) else {
+        return Vec::new();
+    };
+    let file_name = file_name.to_string_lossy();
+
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+
+    let mut artifacts: Vec<ConfigArtifact> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let suffix = name.strip_prefix(file_name.as_ref())?;
+            let kind = if suffix == ".backup" {
+                "backup"
+            } else if suffix.starts_with(".backup_") {
+                "timestamped_backup"
+            } else if suffix.starts_with(".manual_backup_") {
+                "manual_backup"
+            } else if suffix == ".broken" {
+                "broken"
+            } else {
+                return None;
+            };
+
+            let metadata = entry.metadata().ok()?;
+            let modified = metadata
+                .modified()
+                .ok()
+                .and_then(|time| time.duration_since(std::time::UNIX_EPOCH).ok())
+                .map(|duration| duration.as_secs())
+                .unwrap_or(0);
+
+            Some(ConfigArtifact {
+                path: entry.path().to_string_lossy().to_string(),
+                kind: kind.to_string(),
+                size: metadata.len(),
+                modified,
+            })
+        })
+        .collect();
+
+    artifacts.sort_by(|a, b| b.modified.cmp(&a.modified));
+    artifacts
+}
+
+// Match a file name against a pattern where `*` stands for any run of characters
+fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let parts: Vec<&str> = pattern.split('*').collect();
+    if parts.len() == 1 {
+        return pattern == text;
+    }
+
+    let (first, last) = (parts[0], parts[parts.len() - 1]);
+    if !text.starts_with(first) || text.len() < first.len() + last.len() || !text.ends_with(last) {
+        return false;
+    }
+
+    let mut rest = &text[first.len()..text.len() - last.len()];
+    for part in &parts[1..parts.len() - 1] {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    true
+}
+
+fn read_backup_info(backup_path: String) -> Result<BackupInfo, String> {
+    let metadata =
+        fs::metadata(&backup_path).map_err(|e| format!("Failed to get backup metadata: {}", e))?;
+
+    let size = metadata.len();
+    let created = metadata
+        .modified()
+        .map(|time| {
+            let duration = time
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default();
+            let datetime = std::time::SystemTime::UNIX_EPOCH + duration;
+            format!("{:?}", datetime) // Simple formatting for now
+        })
+        .unwrap_or_else(|_| "Unknown".to_string());
+
+    // Validate backup by trying to parse it
+    let is_valid = match fs::read_to_string(&backup_path) {
+        Ok(content) => {
+            serde_json::from_str::<ClaudeConfig>(normalize_config_content(&content)).is_ok()
+        }
+        Err(_) => false,
+    };
+
+    Ok(BackupInfo {
+        path: backup_path,
+        created,
+        size,
+        is_valid,
+    })
+}
+
+// How long a prepare_restore confirmation token stays valid
+const RESTORE_TOKEN_TTL_SECS: u64 = 60;
+
+// A restore approved by prepare_restore, waiting for restore_from_backup to confirm it
+#[derive(Debug, Clone)]
+pub struct PendingRestore {
+    config_path: String,
+    backup_path: String,
+    expires_at: std::time::Instant,
+}
+
+// First step of a restore: validate the backup, preview the server changes and hand out
+// a single-use token that restore_from_backup must present within the TTL
+#[tauri::command]
+async fn prepare_restore(
+    state: tauri::State<'_, AppState>,
+    custom_path: Option<String>,
+    backup_path: Option<String>,
+) -> Result<RestorePreview, String> {
+    internal_prepare_restore(&state, custom_path, backup_path).await
+}
+
+#[tauri::command]
+async fn restore_from_backup(
+    state: tauri::State<'_, AppState>,
+    confirmation_token: String,
+    app_handle: tauri::AppHandle,
+) -> Result<SaveResult, String> {
+    internal_restore_from_backup(&state, confirmation_token, Some(&app_handle)).await
+}
+
+async fn internal_prepare_restore(
+    state: &AppState,
+    custom_path: Option<String>,
+    backup_path: Option<String>,
+) -> Result<RestorePreview, String> {
+    let config_path = resolve_config_path(custom_path)?;
+    let default_backup_path = format!("{}.backup", config_path);
+    let backup_path = match backup_path {
+        Some(path) if !path.trim().is_empty() => path,
+        _ => default_backup_path.clone(),
+    };
+
+    if !Path::new(&backup_path).exists() {
+        return Err("No backup file found".to_string());
+    }
+
+    // Only this config's own backups can be restored, never an arbitrary file
+    let is_own_backup = backup_path == default_backup_path
+        || list_backups(Some(config_path.clone()))?
+            .iter()
+            .any(|backup| backup.path == backup_path);
+    if !is_own_backup {
+        return Err(format!(
+            "'{}' is not a backup of the current config; pick one from list_backups",
+            backup_path
+        ));
+    }
+
+    // Validate backup before restoring
+    let backup_content = fs::read_to_string(&backup_path)
+        .map_err(|e| format!("Failed to read backup file: {}", e))?;
+    let backup: ClaudeConfig = serde_json::from_str(normalize_config_content(&backup_content))
+        .map_err(|_| "Backup file is corrupted or invalid")?;
+
+    // A missing or broken live config counts as empty for the preview
+    let current: ClaudeConfig = fs::read_to_string(&config_path)
+        .ok()
+        .and_then(|content| serde_json::from_str(normalize_config_content(&content)).ok())
+        .unwrap_or_default();
+
+    let change = ConfigChange::between(&current, &backup);
+
+    let token = random_hex_token(16);
+    {
+        let mut pending = state.pending_restores.write().await;
+        let now = std::time::Instant::now();
+        pending.retain(|_, restore| restore.expires_at > now);
+        pending.insert(
+            token.clone(),
+            PendingRestore {
+                config_path,
+                backup_path: backup_path.clone(),
+                expires_at: now + std::time::Duration::from_secs(RESTORE_TOKEN_TTL_SECS),
+            },
+        );
+    }
+
+    Ok(RestorePreview {
+        token,
+        expires_in_seconds: RESTORE_TOKEN_TTL_SECS,
+        backup_path,
+        added: change.added,
+        removed: change.removed,
+        changed: change.updated,
+    })
+}
+
+#[derive(Debug, Serialize)]
+struct ChangedServer {
+    name: String,
+    fields: Vec<ServerFieldChange>,
+}
+
+#[derive(Debug, Serialize)]
+struct BackupDiff {
+    added: Vec<String>,
+    removed: Vec<String>,
+    changed: Vec<ChangedServer>,
+}
+
+#[tauri::command]
+fn diff_backups(from_path: String, to_path: String) -> Result<BackupDiff, String> {
+    let read_backup = |path: &str| -> Result<ClaudeConfig, String> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read backup '{}': {}", path, e))?;
+        serde_json::from_str(normalize_config_content(&content))
+            .map_err(|_| format!("Backup '{}' is corrupted or invalid", path))
+    };
+    let from = read_backup(&from_path)?;
+    let to = read_backup(&to_path)?;
+
+    let change = ConfigChange::between(&from, &to);
+    let changed = change
+        .updated
+        .into_iter()
+        .map(|name| {
+            let mut fields = Vec::new();
+            if let (Some((from_enabled, from_server)), Some((to_enabled, to_server))) =
+                (from.find_server(&name), to.find_server(&name))
+            {
+                if from_enabled != to_enabled {
+                    fields.push(ServerFieldChange {
+                        field: "enabled".to_string(),
+                        from: serde_json::json!(from_enabled),
+                        to: serde_json::json!(to_enabled),
+                    });
+                }
+                fields.extend(diff_server_fields(from_server, to_server));
+            }
+            ChangedServer { name, fields }
+        })
+        .collect();
+
+    Ok(BackupDiff {
+        added: change.added,
+        removed: change.removed,
+        changed,
+    })
+}
+
+// The fields that differ between two versions of a server, shared by diff_backups and
+// compare_with_preset
+fn diff_server_fields(from: &McpServer, to: &McpServer) -> Vec<ServerFieldChange> {
+    let mut fields = Vec::new();
+    let mut push = |field: String, from: serde_json::Value, to: serde_json::Value| {
+        if from != to {
+            fields.push(ServerFieldChange { field, from, to });
+        }
+    };
+
+    push(
+        "command".to_string(),
+        serde_json::json!(from.command),
+        serde_json::json!(to.command),
+    );
+    push(
+        "args".to_string(),
+        serde_json::json!(from.args),
+        serde_json::json!(to.args),
+    );
+    push(
+        "cwd".to_string(),
+        serde_json::json!(from.cwd),
+        serde_json::json!(to.cwd),
+    );
+
+    let from_env = from.env.clone().unwrap_or_default();
+    let to_env = to.env.clone().unwrap_or_default();
+    let keys: std::collections::BTreeSet<&String> = from_env.keys().chain(to_env.keys()).collect();
+    let redacted = |value: Option<&String>| {
+        value.map_or(serde_json::Value::Null, |_| serde_json::json!("<redacted>"))
+    };
+    for key in keys {
+        let (from_value, to_value) = (from_env.get(key), to_env.get(key));
+        if from_value != to_value {
+            fields.push(ServerFieldChange {
+                field: format!("env.{}", key),
+                from: redacted(from_value),
+                to: redacted(to_value),
+            });
+        }
+    }
+
+    fields
+}
+
+async fn internal_restore_from_backup(
+    state: &AppState,
+    confirmation_token: String,
+    app_handle: Option<&tauri::AppHandle>,
+) -> Result<SaveResult, String> {
+    if state.is_read_only().await {
+        return Ok(read_only_result());
+    }
+
+    let _write_guard = state.lock_config_writes().await;
+    let pending = state
+        .pending_restores
+        .write()
+        .await
+        .remove(&confirmation_token);
+    let PendingRestore {
+        config_path,
+        backup_path,
+        expires_at,
+    } = match pending {
+        Some(pending) => pending,
+        None => {
+            return Ok(SaveResult {
+                success: false,
+                message: "Unknown confirmation token; call prepare_restore first".to_string(),
+                path: None,
+            });
+        }
+    };
+
+    if expires_at <= std::time::Instant::now() {
+        return Ok(SaveResult {
+            success: false,
+            message: "Confirmation token expired; call prepare_restore again".to_string(),
+            path: None,
+        });
+    }
+
+    // The backup may have changed since it was previewed, so validate it again
+    let backup_content = fs::read_to_string(&backup_path)
+        .map_err(|e| format!("Failed to read backup file: {}", e))?;
+
+    let backup_content = normalize_config_content(&backup_content);
+    let backup: ClaudeConfig =
+        serde_json::from_str(backup_content).map_err(|_| "Backup file is corrupted or invalid")?;
+
+    // Create a backup of the current (potentially broken) file
+    let broken_backup_path = format!("{}.broken", config_path);
+    if Path::new(&config_path).exists() {
+        fs::copy(&config_path, &broken_backup_path)
+            .map_err(|e| format!("Failed to backup current file: {}", e))?;
+    }
+
+    // A missing or broken live config counts as empty, as in the preview
+    let current: ClaudeConfig = fs::read_to_string(&config_path)
+        .ok()
+        .and_then(|content| serde_json::from_str(normalize_config_content(&content)).ok())
+        .unwrap_or_default();
+
+    // Write the backup's whole document, not just its servers, so its other top-level
+    // keys come back too. Going through the save path keeps the cache, the undo stack
+    // and the GUI in step.
+    state
+        .write_config_document(&config_path, backup_content, &backup, "Restore from backup")
+        .await?;
+
+    if let Some(handle) = app_handle {
+        state
+            .emit_event(
+                handle,
+                "config-changed",
+                ConfigChange::between(&current, &backup).payload(),
+            )
+            .await;
+    }
+
+    Ok(SaveResult {
+        success: true,
+        message: "Configuration restored from backup successfully".to_string(),
+        path: Some(config_path),
+    })
+}
+
+#[tauri::command]
+async fn undo_last_change(
+    state: tauri::State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+) -> Result<SaveResult, String> {
+    internal_apply_snapshot(&state, true, Some(&app_handle)).await
+}
+
+#[tauri::command]
+async fn redo_last_change(
+    state: tauri::State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+) -> Result<SaveResult, String> {
+    internal_apply_snapshot(&state, false, Some(&app_handle)).await
+}
+
+// Pop a snapshot from the undo (or redo) stack, write it back and push the current
+// file content onto the opposite stack so the step can be reversed again
+async fn internal_apply_snapshot(
+    state: &AppState,
+    undo: bool,
+    app_handle: Option<&tauri::AppHandle>,
+) -> Result<SaveResult, String> {
+    if state.is_read_only().await {
+        return Ok(read_only_result());
+    }
+
+    let (source, target) = if undo {
+        (&state.undo_stack, &state.redo_stack)
+    } else {
+        (&state.redo_stack, &state.undo_stack)
+    };
+    let action = if undo { "undo" } else { "redo" };
+
+    let _write_guard = state.lock_config_writes().await;
+    let snapshot = match source.write().await.pop() {
+        Some(snapshot) => snapshot,
+        None => {
+            return Ok(SaveResult {
+                success: false,
+                message: format!("Nothing to {}", action),
+                path: None,
+            });
+        }
+    };
+
+    // Validate the snapshot before restoring, like restore_from_backup does
+    let config: ClaudeConfig = match serde_json::from_str(&snapshot.content) {
+        Ok(config) => config,
+        Err(_) => {
+            return Ok(SaveResult {
+                success: false,
+                message: format!("Cannot {}: saved state is corrupted or invalid", action),
+                path: None,
+            });
+        }
+    };
+
+    let current_content = fs::read_to_string(&snapshot.config_path)
+        .map_err(|e| format!("Failed to read current config: {}", e))?;
+
+    if let Err(e) = write_config_atomically(&snapshot.config_path, &snapshot.content) {
+        // Put the snapshot back so nothing is lost
+        source.write().await.push(snapshot);
+        return Err(format!("Failed to write config: {}", e));
+    }
+
+    let change = serde_json::from_str::<ClaudeConfig>(&current_content)
+        .map(|current| ConfigChange::between(&current, &config))
+        .unwrap_or_default();
+
+    {
+        let mut target_stack = target.write().await;
+        target_stack.push(ConfigSnapshot {
+            operation: snapshot.operation.clone(),
+            config_path: snapshot.config_path.clone(),
+            content: current_content,
+        });
+        if target_stack.len() > UNDO_STACK_LIMIT {
+            target_stack.remove(0);
+        }
+    }
+
+    state
+        .config_cache
+        .write()
+        .await
+        .insert(snapshot.config_path.clone(), config);
+
+    // Emit event for GUI updates
+    if let Some(handle) = app_handle {
+        state
+            .emit_event(handle, "config-changed", change.payload())
+            .await;
+    }
+
+    Ok(SaveResult {
+        success: true,
+        message: format!(
+            "{}: {}",
+            if undo { "Undid" } else { "Redid" },
+            snapshot.operation
+        ),
+        path: Some(snapshot.config_path),
+    })
+}
+
+#[tauri::command]
+fn open_file_location(path: String) -> Result<(), String> {
+    use std::process::Command;
+
+    #[cfg(target_os = "windows")]
+    {
+        Command::new("explorer")
+            .args(["/select,", &path])
+            .spawn()
+            .map_err(|e| format!("Failed to open file location: {}", e))?;
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        Command::new("open")
+            .args(["-R", &path])
+            .spawn()
+            .map_err(|e| format!("Failed to open file location: {}", e))?;
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        // Try different file managers for Linux
+        let file_managers = ["nautilus", "dolphin", "thunar", "pcmanfm", "nemo"];
+        let mut success = false;
+
+        for manager in &file_managers {
+            if let Ok(_) = Command::new(manager).arg(&path).spawn() {
+                success = true;
+                break;
+            }
+        }
+
+        if !success {
+            // Fallback to opening the parent directory with xdg-open
+            let parent_path = std::path::Path::new(&path)
+                .parent()
+                .unwrap_or_else(|| std::path::Path::new("/"))
+                .to_string_lossy();
+
+            Command::new("xdg-open")
+                .arg(parent_path.as_ref())
+                .spawn()
+                .map_err(|e| format!("Failed to open file location: {}", e))?;
+        }
+    }
+
+    Ok(())
+}
+
+#[tauri::command]
+fn create_manual_backup(custom_path: Option<String>) -> Result<SaveResult, String> {
+    let config_path = resolve_config_path(custom_path)?;
+
+    if !Path::new(&config_path).exists() {
+        return Ok(SaveResult {
+            success: false,
+            message: "Configuration file does not exist".to_string(),
+            path: None,
+        });
+    }
+
+    // Create timestamped backup
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+
+    let manual_backup_path = format!("{}.manual_backup_{}", config_path, timestamp);
+
+    fs::copy(&config_path, &manual_backup_path)
+        .map_err(|e| format!("Failed to create manual backup: {}", e))?;
+
+    Ok(SaveResult {
+        success: true,
+        message: format!("Manual backup created: {}", manual_backup_path),
+        path: None,
+    })
+}
+
+// Profiles are named copies of the server maps stored as
+// `{settings_dir}/profiles/{name}.json`
+fn profiles_dir() -> Result<std::path::PathBuf, String> {
+    Ok(settings_dir()?.join("profiles"))
+}
+
+fn profile_path(name: &str) -> Result<std::path::PathBuf, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Profile name cannot be empty".to_string());
+    }
+    // The name becomes a file name, so keep it from escaping the profiles directory
+    if !name
+        .chars()
+        .all(|c| c.is_alphanumeric() || matches!(c, ' ' | '-' | '_' | '.'))
+        || name.starts_with('.')
+    {
+        return Err(format!(
+            "Profile name '{}' may only contain letters, digits, spaces, '-', '_' and '.'",
+            name
+        ));
+    }
+
+    Ok(profiles_dir()?.join(format!("{}.json", name)))
+}
+
+#[tauri::command]
+async fn save_profile(
+    state: tauri::State<'_, AppState>,
+    name: String,
+) -> Result<SaveResult, String> {
+    let path = profile_path(&name)?;
+    let (config, _) = state.load_config(None).await?;
+
+    let dir = profiles_dir()?;
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create profiles directory: {}", e))?;
+
+    let profile_json = serde_json::to_string_pretty(&config)
+        .map_err(|e| format!("Failed to serialize profile: {}", e))?;
+    fs::write(&path, profile_json).map_err(|e| format!("Failed to write profile: {}", e))?;
+
+    Ok(SaveResult {
+        success: true,
+        message: format!("Profile '{}' saved", name.trim()),
+        path: None,
+    })
+}
+
+// Replace the live servers with the profile's; save_config backs up the current file first
+#[tauri::command]
+async fn load_profile(
+    state: tauri::State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+    name: String,
+) -> Result<SaveResult, String> {
+    if state.is_read_only().await {
+        return Ok(read_only_result());
+    }
+
+    let path = profile_path(&name)?;
+    if !path.exists() {
+        return Ok(SaveResult {
+            success: false,
+            message: format!("Profile '{}' not found", name.trim()),
+            path: None,
+        });
+    }
+
+    let content =
+        fs::read_to_string(&path).map_err(|e| format!("Failed to read profile: {}", e))?;
+    let raw_profile: serde_json::Value = serde_json::from_str(normalize_config_content(&content))
+        .map_err(|e| format!("Failed to parse profile: {}", e))?;
+
+    // A profile is written over the live config, so it gets the same checks as a config
+    // read from disk
+    let schema_errors = validate_against_schema(&raw_profile)?;
+    if !schema_errors.is_empty() {
+        let details: Vec<String> = schema_errors
+            .iter()
+            .map(|error| format!("{} at {}", error.message, error.path))
+            .collect();
+        return Ok(SaveResult {
+            success: false,
+            message: format!("Profile validation failed: {}", details.join("; ")),
+            path: None,
+        });
+    }
+    let profile: ClaudeConfig = serde_json::from_value(raw_profile)
+        .map_err(|e| format!("Failed to parse profile: {}", e))?;
+    if let Err(validation_error) = validate_claude_config_structure(&profile) {
+        return Ok(SaveResult {
+            success: false,
+            message: format!("Profile validation failed: {}", validation_error),
+            path: None,
+        });
+    }
+
+    let _write_guard = state.lock_config_writes().await;
+    let (previous, config_path) = state.load_or_create_config(None, true).await?;
+    let operation = format!("Load profile '{}'", name.trim());
+    state
+        .save_config(&config_path, &profile, &operation)
+        .await?;
+    state
+        .emit_event(
+            &app_handle,
+            "config-changed",
+            ConfigChange::between(&previous, &profile).payload(),
+        )
+        .await;
+
+    Ok(SaveResult {
+        success: true,
+        message: format!("Profile '{}' loaded", name.trim()),
+        path: Some(config_path.clone()),
+    })
+}
+
+#[tauri::command]
+fn list_profiles() -> Result<Vec<ProfileInfo>, String> {
+    let entries = match fs::read_dir(profiles_dir()?) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(format!("Failed to read profiles directory: {}", e)),
+    };
+
+    let mut profiles: Vec<ProfileInfo> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let path = entry.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+                return None;
+            }
+            let name = path.file_stem()?.to_string_lossy().to_string();
+
+            // Unreadable profiles are still listed so they can be deleted
+            let server_count = fs::read_to_string(&path)
+                .ok()
+                .and_then(|content| serde_json::from_str::<ClaudeConfig>(&content).ok())
+                .map(|config| config.mcp_servers.len() + config.disabled_servers.len())
+                .unwrap_or(0);
+            let modified = entry
+                .metadata()
+                .and_then(|metadata| metadata.modified())
+                .ok()
+                .and_then(|time| time.duration_since(std::time::UNIX_EPOCH).ok())
+                .map(|duration| duration.as_secs())
+                .unwrap_or(0);
+
+            Some(ProfileInfo {
+                name,
+                server_count,
+                modified,
+            })
+        })
+        .collect();
+
+    profiles.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(profiles)
+}
+
+#[tauri::command]
+fn delete_profile(name: String) -> Result<SaveResult, String> {
+    let path = profile_path(&name)?;
+    if !path.exists() {
+        return Ok(SaveResult {
+            success: false,
+            message: format!("Profile '{}' not found", name.trim()),
+            path: None,
+        });
+    }
+
+    fs::remove_file(&path).map_err(|e| format!("Failed to delete profile: {}", e))?;
+
+    Ok(SaveResult {
+        success: true,
+        message: format!("Profile '{}' deleted", name.trim()),
+        path: None,
+    })
+}
+
+#[tauri::command]
+fn get_settings_path() -> Result<String, String> {
+    Ok(settings_dir()?
+        .join("settings.json")
+        .to_string_lossy()
+        .to_string())
+}
+
+fn settings_dir() -> Result<std::path::PathBuf, String> {
+    Ok(app_data_dir()?.join("mcp-manager"))
+}
+
+// Diagnostic summary for bug reports; lookup failures are reported per field
+#[tauri::command]
+fn get_platform_paths() -> PlatformPaths {
+    let (claude_config_path, claude_config_error) = split_result(get_claude_config_path());
+    let (settings_path, settings_error) = split_result(get_settings_path());
+
+    PlatformPaths {
+        os: env::consts::OS.to_string(),
+        arch: env::consts::ARCH.to_string(),
+        claude_config_exists: claude_config_path
+            .as_deref()
+            .is_some_and(|path| Path::new(path).exists()),
+        claude_config_path,
+        claude_config_error,
+        settings_path,
+        settings_error,
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct ConfigWritability {
+    path: String,
+    exists: bool,
+    writable: bool,
+    // Why the config can't be written, if it can't
+    reason: Option<String>,
+}
+
+// Probe whether edits to the config would succeed, without modifying it. A missing
+// file counts as writable when its directory is.
+#[tauri::command]
+fn check_config_writable(custom_path: Option<String>) -> Result<ConfigWritability, String> {
+    let path = resolve_config_path(custom_path)?;
+    let exists = Path::new(&path).exists();
+
+    let probe = if exists {
+        // Appending nothing leaves the content untouched but fails without write access
+        fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .map(|_| ())
+            .map_err(|e| format!("Config file is not writable: {}", e))
+    } else {
+        match Path::new(&path).parent().filter(|parent| parent.exists()) {
+            Some(parent) => match fs::metadata(parent) {
+                Ok(metadata) if metadata.permissions().readonly() => Err(format!(
+                    "Config directory '{}' is read-only",
+                    parent.display()
+                )),
+                Ok(_) => Ok(()),
+                Err(e) => Err(format!("Failed to read config directory: {}", e)),
+            },
+            // Parent directories are created on first save
+            None => Ok(()),
+        }
+    };
+
+    Ok(ConfigWritability {
+        path,
+        exists,
+        writable: probe.is_ok(),
+        reason: probe.err(),
+    })
+}
+
+fn split_result<T>(result: Result<T, String>) -> (Option<T>, Option<String>) {
+    match result {
+        Ok(value) => (Some(value), None),
+        Err(e) => (None, Some(e)),
+    }
+}
+
+// MCP Server Status
+#[derive(Debug, Serialize, Clone)]
+pub struct McpServerStatus {
+    pub running: bool,
+    pub port: Option<u16>,
+    pub sse_path: Option<String>,
+    pub post_path: Option<String>,
+    pub url: Option<String>,
+    // Header clients must send when an auth token is configured
+    pub auth_header: Option<String>,
+    pub bind_address: Option<String>,
+    // The HTTP fields above are only set for the SSE transport
+    pub transport: Option<McpTransport>,
+    // The server runs inside the app process, so this is the app's PID
+    pub pid: Option<u32>,
+    pub started_at: Option<u64>,
+    pub uptime_seconds: Option<u64>,
+    // Why the background server task last failed; cleared on a successful start
+    pub last_error: Option<String>,
+}
+
+impl McpServerStatus {
+    fn stopped() -> Self {
+        Self {
+            running: false,
+            port: None,
+            sse_path: None,
+            post_path: None,
+            url: None,
+            auth_header: None,
+            bind_address: None,
+            transport: None,
+            pid: None,
+            started_at: None,
+            uptime_seconds: None,
+            last_error: None,
+        }
+    }
+}
+
+// Maximum number of changes kept for undo/redo
+const UNDO_STACK_LIMIT: usize = 10;
+
+// Raw config file content captured around a mutation, used for undo/redo
+#[derive(Debug, Clone)]
+pub struct ConfigSnapshot {
+    pub operation: String,
+    pub config_path: String,
+    pub content: String,
+}
+
+// A PATH lookup result and when it was made, keyed by command in AppState::command_cache
+type CachedCommand = (Option<std::path::PathBuf>, std::time::Instant);
+
+// Shared state for real-time sync between GUI and MCP server
+#[derive(Debug, Clone)]
+pub struct AppState {
+    // Last config written or observed per config path, so the watcher can tell our own
+    // saves from external edits
+    pub config_cache: Arc<RwLock<HashMap<String, ClaudeConfig>>>,
+    pub settings_cache: Arc<RwLock<AppSettings>>,
+    pub mcp_server_status: Arc<RwLock<McpServerStatus>>,
+    pub mcp_server_cancellation: Arc<RwLock<Option<CancellationToken>>>,
+    pub mcp_server_task: Arc<RwLock<Option<tokio::task::JoinHandle<()>>>>,
+    pub config_watcher_cancellation: Arc<RwLock<Option<CancellationToken>>>,
+    pub undo_stack: Arc<RwLock<Vec<ConfigSnapshot>>>,
+    pub redo_stack: Arc<RwLock<Vec<ConfigSnapshot>>>,
+    // Keyed by confirmation token
+    pub pending_restores: Arc<RwLock<HashMap<String, PendingRestore>>>,
+    pub command_cache: Arc<RwLock<HashMap<String, CachedCommand>>>,
+    // Serializes load-modify-save cycles so concurrent GUI and MCP edits can't
+    // overwrite each other
+    pub config_write_lock: Arc<tokio::sync::Mutex<()>>,
+}
+
+impl AppState {
+    pub fn new() -> Self {
+        Self {
+            config_cache: Arc::new(RwLock::new(HashMap::new())),
+            settings_cache: Arc::new(RwLock::new(AppSettings::default())),
+            mcp_server_status: Arc::new(RwLock::new(McpServerStatus::stopped())),
+            mcp_server_cancellation: Arc::new(RwLock::new(None)),
+            mcp_server_task: Arc::new(RwLock::new(None)),
+            config_watcher_cancellation: Arc::new(RwLock::new(None)),
+            undo_stack: Arc::new(RwLock::new(Vec::new())),
+            redo_stack: Arc::new(RwLock::new(Vec::new())),
+            pending_restores: Arc::new(RwLock::new(HashMap::new())),
+            command_cache: Arc::new(RwLock::new(HashMap::new())),
+            config_write_lock: Arc::new(tokio::sync::Mutex::new(())),
+        }
+    }
+
+    // Hold the returned guard from before the config is read until after it is
+    // written. Not reentrant, so the guarded functions must not call each other.
+    pub async fn lock_config_writes(&self) -> tokio::sync::MutexGuard<'_, ()> {
+        self.config_write_lock.lock().await
+    }
+
+    // resolve_command_path, reusing results younger than the configured TTL so validating
+    // a whole config doesn't rescan PATH for every server
+    pub async fn resolve_command_cached(&self, command: &str) -> Option<std::path::PathBuf> {
+        let ttl_secs = self.settings_cache.read().await.command_cache_ttl_secs;
+        let ttl = std::time::Duration::from_secs(ttl_secs);
+
+        if let Some((resolved, resolved_at)) = self.command_cache.read().await.get(command) {
+            if resolved_at.elapsed() < ttl {
+                return resolved.clone();
+            }
+        }
+
+        let resolved = resolve_command_path(command);
+        let mut cache = self.command_cache.write().await;
+        cache.retain(|_, (_, resolved_at)| resolved_at.elapsed() < ttl);
+        cache.insert(
+            command.to_string(),
+            (resolved.clone(), std::time::Instant::now()),
+        );
+        resolved
+    }
+
+    // Returns the config together with the path it was read from; pass that path to
+    // save_config so a write always lands in the file that was loaded
+    pub async fn load_config(
+        &self,
+        custom_path: Option<String>,
+    ) -> Result<(ClaudeConfig, String), String> {
+        let config_path = resolve_config_path(custom_path)?;
+
+        let file_content = fs::read_to_string(&config_path)
+            .map_err(|e| ConfigIoError::from_io_error(&config_path, &e).to_error_string())?;
+
+        let file_content = normalize_config_content(&file_content);
+        let raw_config: serde_json::Value = match serde_json::from_str(file_content) {
+            Ok(raw_config) => raw_config,
+            Err(e) => {
+                let mut error_info = analyze_json_error(file_content, &e);
+                let backup_path = format!("{}.backup", config_path);
+                error_info.has_backup = Path::new(&backup_path).exists();
+                let error_json = serde_json::to_string(&error_info).unwrap_or_else(|_| {
+                    format!(
+                        "{{\"error_type\":\"unknown\",\"message\":\"Failed to parse JSON: {}\"}}",
+                        e
+                    )
+                });
+                return Err(format!("JSON_ERROR:{}", error_json));
+            }
+        };
+
+        // Check against the schema first for precise "field X at path Y" messages
+        let schema_errors = validate_against_schema(&raw_config)?;
+        if !schema_errors.is_empty() {
+            let details: Vec<String> = schema_errors
+                .iter()
+                .map(|error| format!("{} at {}", error.message, error.path))
+                .collect();
+            return Err(format!(
+                "Configuration validation failed: {}",
+                details.join("; ")
+            ));
+        }
+
+        let config: ClaudeConfig = serde_json::from_value(raw_config)
+            .map_err(|e| format!("Failed to parse config: {}", e))?;
+
+        if let Err(validation_error) = validate_claude_config_structure(&config) {
+            return Err(format!(
+                "Configuration validation failed: {}",
+                validation_error
+            ));
+        }
+
+        Ok((config, config_path))
+    }
+
+    // Like load_config, but a missing file (e.g. a fresh Claude install) yields an empty
+    // config that the next save_config writes out
+    pub async fn load_or_create_config(
+        &self,
+        custom_path: Option<String>,
+        create_if_missing: bool,
+    ) -> Result<(ClaudeConfig, String), String> {
+        let config_path = resolve_config_path(custom_path.clone())?;
+        if create_if_missing && !Path::new(&config_path).exists() {
+            return Ok((ClaudeConfig::default(), config_path));
+        }
+
+        self.load_config(custom_path).await
+    }
+
+    pub async fn save_config(
+        &self,
+        config_path: &str,
+        config: &ClaudeConfig,
+        operation: &str,
+    ) -> Result<(), String> {
+        let content = serialize_config_preserving_keys(config_path, config)?;
+        self.write_config_document(config_path, &content, config, operation)
+            .await
+    }
+
+    // Write a whole config document, e.g. a restored backup with its own top-level
+    // keys, with the same backup, undo snapshot and cache update as save_config.
+    // `config` must be the servers parsed from `content`.
+    pub async fn write_config_document(
+        &self,
+        config_path: &str,
+        content: &str,
+        config: &ClaudeConfig,
+        operation: &str,
+    ) -> Result<(), String> {
+        // Backstop for mutations that don't check read-only mode up front
+        if self.is_read_only().await {
+            return Err(READ_ONLY_MESSAGE.to_string());
+        }
+
+        if config_path.is_empty() {
+            return Err("Config path not set".to_string());
+        }
+
+        // Create backup (nothing to back up when the file is being created)
+        let config_exists = Path::new(config_path).exists();
+        if config_exists {
+            let retention_count = self.settings_cache.read().await.backup_retention_count;
+            create_backup(config_path, retention_count)?;
+        } else if let Some(parent) = Path::new(config_path).parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create config directory: {}", e))?;
+        }
+        let previous_content = fs::read_to_string(config_path).ok();
+
+        write_config_atomically(config_path, content)
+            .map_err(|e| format!("Failed to write config: {}", e))?;
+
+        if let Some(content) = previous_content {
+            self.push_undo_snapshot(ConfigSnapshot {
+                operation: operation.to_string(),
+                config_path: config_path.to_string(),
+                content,
+            })
+            .await;
+        }
+
+        // Update cache
+        self.config_cache
+            .write()
+            .await
+            .insert(config_path.to_string(), config.clone());
+        Ok(())
+    }
+
+    pub async fn is_read_only(&self) -> bool {
+        self.settings_cache.read().await.read_only
+    }
+
+    // Record the pre-change file content; any new change invalidates the redo history
+    pub async fn push_undo_snapshot(&self, snapshot: ConfigSnapshot) {
+        let mut undo_stack = self.undo_stack.write().await;
+        undo_stack.push(snapshot);
+        if undo_stack.len() > UNDO_STACK_LIMIT {
+            undo_stack.remove(0);
+        }
+        self.redo_stack.write().await.clear();
+    }
+
+    pub async fn emit_event(
+        &self,
+        app_handle: &tauri::AppHandle,
+        event: &str,
+        payload: serde_json::Value,
+    ) {
+        if let Err(e) = app_handle.emit(event, payload) {
+            tracing::warn!(error = %e, event, "Failed to emit event");
+        }
+    }
+}
+
+// Internal shared functions that both Tauri commands and MCP tools can use
+async fn internal_parse_claude_json(
+    state: &AppState,
+    custom_path: Option<String>,
+) -> Result<Vec<McpServerInfo>, String> {
+    let (config, _) = state.load_config(custom_path).await?;
+
+    let enabled_servers = config.mcp_servers.into_iter().map(|entry| (entry, true));
+    let disabled_servers = config
+        .disabled_servers
+        .into_iter()
+        .map(|entry| (entry, false));
+
+    let mut servers = Vec::new();
+    for ((name, server), enabled) in enabled_servers.chain(disabled_servers) {
+        let env = server.env.unwrap_or_default();
+        servers.push(McpServerInfo {
+            name,
+            command: server.command,
+            args: server.args,
+            env,
+            cwd: server.cwd,
+            enabled,
+        });
+    }
+
+    // Sort servers alphabetically by name
+    servers.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(servers)
+}
+
+// An add, update or delete of one server. The real commands and their dry runs apply
+// it through apply_server_change, so both accept and refuse the same edits.
+enum ServerChange {
+    // `replace` lets the add take over an existing server of the same name
+    Add { server: McpServer, replace: bool },
+    Update(McpServer),
+    Delete,
+}
+
+// Trim and check the env names and cwd of an edit, and turn it into the stored form
+fn normalize_server_edit(server_data: McpServerEdit) -> Result<McpServer, String> {
+    let env = normalize_env_keys(server_data.env)?;
+    Ok(McpServer {
+        command: server_data.command,
+        args: server_data.args,
+        env: if env.is_empty() { None } else { Some(env) },
+        cwd: normalize_cwd(server_data.cwd)?,
+    })
+}
+
+// Apply `change` to `config`, looking in both the enabled and disabled servers. Returns
+// the server's previous entry, or the message for a change that has to be refused.
+fn apply_server_change(
+    config: &mut ClaudeConfig,
+    name: &str,
+    change: ServerChange,
+) -> Result<Option<McpServer>, String> {
+    let previous = config
+        .mcp_servers
+        .get(name)
+        .or(config.disabled_servers.get(name))
+        .cloned();
+
+    match change {
+        ServerChange::Add { server, replace } => {
+            if previous.is_some() && !replace {
+                return Err(format!("Server '{}' already exists", name));
+            }
+            // A replaced server ends up enabled, like a fresh install
+            config.disabled_servers.shift_remove(name);
+            config.mcp_servers.insert(name.to_string(), server);
+        }
+        ServerChange::Update(server) => {
+            // Disabled servers are edited in place so they stay disabled
+            let Some(existing) = config
+                .mcp_servers
+                .get_mut(name)
+                .or(config.disabled_servers.get_mut(name))
+            else {
+                return Err(format!("Server '{}' not found", name));
+            };
+            *existing = server;
+        }
+        ServerChange::Delete => {
+            if config.mcp_servers.shift_remove(name).is_none()
+                && config.disabled_servers.shift_remove(name).is_none()
+            {
+                return Err(format!("Server '{}' not found", name));
+            }
+        }
+    }
+
+    Ok(previous)
+}
+
+// Move the secret env values of a server just written into `config` to the keychain
+fn move_server_secrets_to_keychain(config: &mut ClaudeConfig, name: &str) -> Result<(), String> {
+    let server = config
+        .mcp_servers
+        .get_mut(name)
+        .or(config.disabled_servers.get_mut(name));
+    if let Some(server) = server {
+        if let Some(env) = server.env.take() {
+            server.env = Some(move_secrets_to_keychain(name, env)?);
+        }
+    }
+    Ok(())
+}
+
+async fn internal_add_server(
+    state: &AppState,
+    name: String,
+    server_data: McpServerEdit,
+    custom_path: Option<String>,
+    create_if_missing: bool,
+    app_handle: Option<&tauri::AppHandle>,
+) -> Result<SaveResult, String> {
+    internal_add_server_with_conflict(
+        state,
+        name,
+        server_data,
+        custom_path,
+        create_if_missing,
+        mcp_server::OnConflict::Fail,
+        app_handle,
+    )
+    .await
+}
+
+// Add a server, deciding what happens to an existing one of the same name (enabled or
+// disabled) while holding the write lock, so an overwrite is a single save with one
+// backup and one undo step
+async fn internal_add_server_with_conflict(
+    state: &AppState,
+    name: String,
+    server_data: McpServerEdit,
+    custom_path: Option<String>,
+    create_if_missing: bool,
+    on_conflict: mcp_server::OnConflict,
+    app_handle: Option<&tauri::AppHandle>,
+) -> Result<SaveResult, String> {
+    if state.is_read_only().await {
+        return Ok(read_only_result());
+    }
+
+    let server = match normalize_server_edit(server_data) {
+        Ok(server) => server,
+        Err(message) => {
+            return Ok(SaveResult {
+                success: false,
+                message,
+                path: None,
+            })
+        }
+    };
+
+    let _write_guard = state.lock_config_writes().await;
+    let (mut config, config_path) = state
+        .load_or_create_config(custom_path, create_if_missing)
+        .await?;
+
+    let exists =
+        config.mcp_servers.contains_key(&name) || config.disabled_servers.contains_key(&name);
+    if exists && on_conflict == mcp_server::OnConflict::Keep {
+        return Ok(SaveResult {
+            success: true,
+            message: format!("Server '{}' already exists and was kept", name),
+            path: None,
+        });
+    }
+
+    let change = ServerChange::Add {
+        server,
+        replace: on_conflict == mcp_server::OnConflict::Overwrite,
+    };
+    if let Err(message) = apply_server_change(&mut config, &name, change) {
+        return Ok(SaveResult {
+            success: false,
+            message,
+            path: None,
+        });
+    }
+    if state.settings_cache.read().await.secrets_in_keychain {
+        move_server_secrets_to_keychain(&mut config, &name)?;
+    }
+
+    if exists {
+        state
+            .save_config(&config_path, &config, &format!("Replace server '{}'", name))
+            .await?;
+        record_server_metadata(&config_path, &name);
+
+        if let Some(handle) = app_handle {
+            state
+                .emit_event(
+                    handle,
+                    "server-updated",
+                    serde_json::json!({ "name": name }),
+                )
+                .await;
+            state
+                .emit_event(
+                    handle,
+                    "config-changed",
+                    ConfigChange::updated(&name).payload(),
+                )
+                .await;
+        }
+
+        return Ok(SaveResult {
+            success: true,
+            message: format!("Server '{}' replaced successfully", name),
+            path: Some(config_path),
+        });
+    }
+
+    state
+        .save_config(&config_path, &config, &format!("Add server '{}'", name))
+        .await?;
+    record_server_metadata(&config_path, &name);
+
+    // Emit event for GUI updates
+    if let Some(handle) = app_handle {
+        state
+            .emit_event(handle, "server-added", serde_json::json!({ "name": name }))
+            .await;
+        state
+            .emit_event(
+                handle,
+                "config-changed",
+                ConfigChange::added(&name).payload(),
+            )
+            .await;
+    }
+
+    Ok(SaveResult {
+        success: true,
+        message: format!("Server '{}' added successfully", name),
+        path: Some(config_path),
+    })
+}
+
+// Install a preset as a new server, reporting `install-progress` stages
+// (validating, pulling, writing, done) to the GUI when an app handle is given
+async fn internal_install_preset(
+    state: &AppState,
+    preset_name: String,
+    api_keys: HashMap<String, String>,
+    placeholders: HashMap<String, String>,
+    pull_image: bool,
+    on_conflict: mcp_server::OnConflict,
+    app_handle: Option<&tauri::AppHandle>,
+) -> Result<SaveResult, String> {
+    let result = run_preset_install(
+        state,
+        &preset_name,
+        api_keys,
+        placeholders,
+        pull_image,
+        on_conflict,
+        app_handle,
+    )
+    .await;
+
+    let success = matches!(&result, Ok(save_result) if save_result.success);
+    emit_install_progress(
+        state,
+        app_handle,
+        &preset_name,
+        "done",
+        serde_json::json!({ "success": success }),
+    )
+    .await;
+
+    result
+}
+
+// The `mcp-manager` preset connects back to this app's own MCP server
+const SELF_PRESET_NAME: &str = "mcp-manager";
+
+// Swap the preset's default `--sse` URL for one using the configured port and SSE
+// path, so the installed server doesn't point at a port nothing listens on
+fn point_self_preset_at_settings(args: &mut [String], settings: &AppSettings) {
+    let url = format!(
+        "http://localhost:{}{}",
+        settings.mcp_server_port, settings.mcp_sse_path
+    );
+    if let Some(arg) = args
+        .iter_mut()
+        .skip_while(|arg| arg.as_str() != "--sse")
+        .nth(1)
+    {
+        *arg = url;
+    }
+}
+
+async fn run_preset_install(
+    state: &AppState,
+    preset_name: &str,
+    api_keys: HashMap<String, String>,
+    placeholders: HashMap<String, String>,
+    pull_image: bool,
+    on_conflict: mcp_server::OnConflict,
+    app_handle: Option<&tauri::AppHandle>,
+) -> Result<SaveResult, String> {
+    emit_install_progress(
+        state,
+        app_handle,
+        preset_name,
+        "validating",
+        serde_json::json!({}),
+    )
+    .await;
+
+    let Some(preset) = get_preset_server_by_name(preset_name.to_string()) else {
+        return Ok(SaveResult {
+            success: false,
+            message: format!("Preset server '{}' not found", preset_name),
+            path: None,
+        });
+    };
+
+    // Skip a pointless docker pull for a server that will be kept anyway; the locked
+    // check in internal_add_server_with_conflict is the one that counts
+    if on_conflict == mcp_server::OnConflict::Keep {
+        let (config, _) = state.load_or_create_config(None, true).await?;
+        if config.mcp_servers.contains_key(&preset.name)
+            || config.disabled_servers.contains_key(&preset.name)
+        {
+            return Ok(SaveResult {
+                success: true,
+                message: format!("Server '{}' already exists and was kept", preset.name),
+                path: None,
+            });
+        }
+    }
+
+    let mut args = match preset.substitute_arg_placeholders(&placeholders) {
+        Ok(args) => args,
+        Err(message) => {
+            return Ok(SaveResult {
+                success: false,
+                message,
+                path: None,
+            })
+        }
+    };
+    if preset.name == SELF_PRESET_NAME {
+        point_self_preset_at_settings(&mut args, &state.settings_cache.read().await);
+    }
+
+    if pull_image && preset.server_type == ServerType::Docker {
+        let image = match docker_run_image(&args) {
+            Ok(image) => image.to_string(),
+            Err(message) => {
+                return Ok(SaveResult {
+                    success: false,
+                    message,
+                    path: None,
+                })
+            }
+        };
+
+        emit_install_progress(
+            state,
+            app_handle,
+            preset_name,
+            "pulling",
+            serde_json::json!({ "image": image }),
+        )
+        .await;
+
+        let output = tokio::process::Command::new("docker")
+            .args(["pull", &image])
+            .output()
+            .await
+            .map_err(|e| format!("Failed to run docker pull: {}", e))?;
+        if !output.status.success() {
+            return Ok(SaveResult {
+                success: false,
+                message: format!(
+                    "Failed to pull image '{}': {}",
+                    image,
+                    String::from_utf8_lossy(&output.stderr).trim()
+                ),
+                path: None,
+            });
+        }
+    }
+
+    emit_install_progress(
+        state,
+        app_handle,
+        preset_name,
+        "writing",
+        serde_json::json!({}),
+    )
+    .await;
+
+    let mut env = preset.env.unwrap_or_default();
+    env.extend(api_keys);
+
+    let server_data = McpServerEdit {
+        command: preset.command,
+        args,
+        env,
+        cwd: None,
+    };
+
+    internal_add_server_with_conflict(
+        state,
+        preset.name,
+        server_data,
+        None,
+        true,
+        on_conflict,
+        app_handle,
+    )
+    .await
+}
+
+async fn emit_install_progress(
+    state: &AppState,
+    app_handle: Option<&tauri::AppHandle>,
+    preset_name: &str,
+    stage: &str,
+    extra: serde_json::Value,
+) {
+    let Some(handle) = app_handle else {
+        return;
+    };
+
+    let mut payload = serde_json::json!({ "name": preset_name, "stage": stage });
+    if let (Some(payload), serde_json::Value::Object(extra)) = (payload.as_object_mut(), extra) {
+        payload.extend(extra);
+    }
+    state.emit_event(handle, "install-progress", payload).await;
+}
+
+async fn internal_delete_server(
+    state: &AppState,
+    name: String,
+    custom_path: Option<String>,
+    app_handle: Option<&tauri::AppHandle>,
+) -> Result<SaveResult, String> {
+    if state.is_read_only().await {
+        return Ok(read_only_result());
+    }
+
+    let _write_guard = state.lock_config_writes().await;
+    let (mut config, config_path) = state.load_config(custom_path).await?;
+
+    if let Err(message) = apply_server_change(&mut config, &name, ServerChange::Delete) {
+        return Ok(SaveResult {
+            success: false,
+            message,
+            path: None,
+        });
+    }
+
+    state
+        .save_config(&config_path, &config, &format!("Delete server '{}'", name))
+        .await?;
+    remove_server_metadata(&config_path, &name);
+
+    // Emit event for GUI updates
+    if let Some(handle) = app_handle {
+        state
+            .emit_event(
+                handle,
+                "server-deleted",
+                serde_json::json!({ "name": name }),
+            )
+            .await;
+        state
+            .emit_event(
+                handle,
+                "config-changed",
+                ConfigChange::removed(&name).payload(),
+            )
+            .await;
+    }
+
+    Ok(SaveResult {
+        success: true,
+        message: format!("Server '{}' deleted successfully", name),
+        path: Some(config_path),
+    })
+}
+
+async fn internal_update_server(
+    state: &AppState,
+    name: String,
+    server_data: McpServerEdit,
+    custom_path: Option<String>,
+    app_handle: Option<&tauri::AppHandle>,
+) -> Result<SaveResult, String> {
+    if state.is_read_only().await {
+        return Ok(read_only_result());
+    }
+
+    let server = match normalize_server_edit(server_data) {
+        Ok(server) => server,
+        Err(message) => {
+            return Ok(SaveResult {
+                success: false,
+                message,
+                path: None,
+            })
+        }
+    };
+
+    let _write_guard = state.lock_config_writes().await;
+    let (mut config, config_path) = state.load_config(custom_path).await?;
+
+    let previous = match apply_server_change(&mut config, &name, ServerChange::Update(server)) {
+        Ok(previous) => previous,
+        Err(message) => {
+            return Ok(SaveResult {
+                success: false,
+                message,
+                path: None,
+            })
+        }
+    };
+    if state.settings_cache.read().await.secrets_in_keychain {
+        move_server_secrets_to_keychain(&mut config, &name)?;
+    }
+    let changed = previous.as_ref()
+        != config
+            .mcp_servers
+            .get(&name)
+            .or(config.disabled_servers.get(&name));
+
+    state
+        .save_config(&config_path, &config, &format!("Update server '{}'", name))
+        .await?;
+    record_server_metadata(&config_path, &name);
+
+    // Emit event for GUI updates; an update that changed nothing reports no servers
+    if let Some(handle) = app_handle {
+        let change = if changed {
+            state
+                .emit_event(
+                    handle,
+                    "server-updated",
+                    serde_json::json!({ "name": name }),
+                )
+                .await;
+            ConfigChange::updated(&name)
+        } else {
+            ConfigChange::default()
+        };
+        state
+            .emit_event(handle, "config-changed", change.payload())
+            .await;
+    }
+
+    Ok(SaveResult {
+        success: true,
+        message: format!("Server '{}' updated successfully", name),
+        path: Some(config_path),
+    })
+}
+
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+// Per-server timestamps live in a `{config_path}.meta.json` sidecar, since the Claude
+// config schema has no room for them. Sidecar failures never fail the config operation.
+fn server_metadata_path(config_path: &str) -> String {
+    format!("{}.meta.json", config_path)
+}
+
+fn load_server_metadata(config_path: &str) -> HashMap<String, ServerMetadata> {
+    fs::read_to_string(server_metadata_path(config_path))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_server_metadata(
+    config_path: &str,
+    metadata: &HashMap<String, ServerMetadata>,
+) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(metadata)
+        .map_err(|e| format!("Failed to serialize server metadata: {}", e))?;
+    fs::write(server_metadata_path(config_path), content)
+        .map_err(|e| format!("Failed to write server metadata: {}", e))
+}
+
+// Mark a server as updated now, recording it as added too if it has no entry yet
+fn record_server_metadata(config_path: &str, name: &str) {
+    let mut metadata = load_server_metadata(config_path);
+    let now = unix_now();
+    metadata
+        .entry(name.to_string())
+        .and_modify(|entry| entry.updated_at = now)
+        .or_insert(ServerMetadata {
+            added_at: now,
+            updated_at: now,
+        });
+
+    if let Err(e) = save_server_metadata(config_path, &metadata) {
+        tracing::warn!("{}", e);
+    }
+}
+
+// Carry a server's timestamps over to its new name
+fn rename_server_metadata(config_path: &str, old_name: &str, new_name: &str) {
+    let mut metadata = load_server_metadata(config_path);
+    let now = unix_now();
+    let added_at = metadata
+        .remove(old_name)
+        .map(|entry| entry.added_at)
+        .unwrap_or(now);
+    metadata.insert(
+        new_name.to_string(),
+        ServerMetadata {
+            added_at,
+            updated_at: now,
+        },
+    );
+
+    if let Err(e) = save_server_metadata(config_path, &metadata) {
+        tracing::warn!("{}", e);
+    }
+}
+
+fn remove_server_metadata(config_path: &str, name: &str) {
+    let mut metadata = load_server_metadata(config_path);
+    if metadata.remove(name).is_some() {
+        if let Err(e) = save_server_metadata(config_path, &metadata) {
+            tracing::warn!("{}", e);
+        }
+    }
+}
+
+// How long a health check waits for the initialize response before killing the server
+const HEALTH_CHECK_TIMEOUT_SECS: u64 = 10;
+
+// Launch a configured server and check that it answers the MCP initialize handshake over stdio
+async fn internal_test_server(state: &AppState, name: String) -> Result<ServerHealth, String> {
+    let (config, _) = state.load_config(None).await?;
+    let server = config
+        .mcp_servers
+        .get(&name)
+        .cloned()
+        .ok_or_else(|| format!("Server '{}' not found", name))?;
+
+    let started = std::time::Instant::now();
+    let outcome = match resolve_launch_env(&server.env.clone().unwrap_or_default()) {
+        Ok(resolved) if !resolved.unresolved.is_empty() => Err(format!(
+            "Unresolved environment references: {}",
+            resolved.unresolved.join(", ")
+        )),
+        Ok(ResolvedEnv { env, .. }) => {
+            match tokio::time::timeout(
+                std::time::Duration::from_secs(HEALTH_CHECK_TIMEOUT_SECS),
+                run_initialize_handshake(&server, &env),
+            )
+            .await
+            {
+                Ok(result) => result,
+                Err(_) => Err(format!(
+                    "No response within {} seconds",
+                    HEALTH_CHECK_TIMEOUT_SECS
+                )),
+            }
+        }
+        Err(e) => Err(e),
+    };
+    let startup_ms = started.elapsed().as_millis() as u64;
+
+    Ok(ServerHealth {
+        name,
+        reachable: outcome.is_ok(),
+        startup_ms,
+        error: outcome.err(),
+    })
+}
+
+// The child is spawned with kill_on_drop, so it is killed whenever this future finishes or is dropped
+async fn run_initialize_handshake(
+    server: &McpServer,
+    env: &HashMap<String, String>,
+) -> Result<(), String> {
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+    let mut command = tokio::process::Command::new(&server.command);
+    command
+        .args(&server.args)
+        .envs(env)
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::null())
+        .kill_on_drop(true);
+    if let Some(cwd) = &server.cwd {
+        command.current_dir(cwd);
+    }
+    let mut child = command
+        .spawn()
+        .map_err(|e| format!("Failed to start '{}': {}", server.command, e))?;
+
+    let mut stdin = child.stdin.take().ok_or("Failed to open server stdin")?;
+    let stdout = child.stdout.take().ok_or("Failed to open server stdout")?;
+
+    let request = serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "initialize",
+        "params": {
+            "protocolVersion": "2024-11-05",
+            "capabilities": {},
+            "clientInfo": {
+                "name": "mcp-manager",
+                "version": env!("CARGO_PKG_VERSION")
+            }
+        }
+    });
+    stdin
+        .write_all(format!("{}\n", request).as_bytes())
+        .await
+        .map_err(|e| format!("Failed to send initialize request: {}", e))?;
+    stdin
+        .flush()
+        .await
+        .map_err(|e| format!("Failed to send initialize request: {}", e))?;
+
+    let mut lines = BufReader::new(stdout).lines();
+    while let Some(line) = lines
+        .next_line()
+        .await
+        .map_err(|e| format!("Failed to read server output: {}", e))?
+    {
+        // Servers may log non-JSON lines to stdout; skip anything that isn't our response
+        let Ok(message) = serde_json::from_str::<serde_json::Value>(&line) else {
+            continue;
+        };
+        if message.get("id") != Some(&serde_json::json!(1)) {
+            continue;
+        }
+        if let Some(error) = message.get("error") {
+            return Err(format!("Server returned an error: {}", error));
+        }
+        if message.get("result").is_some() {
+            return Ok(());
+        }
+    }
+
+    Err("Server exited before responding to initialize".to_string())
+}
+
+// Compute what an add/update/delete would do without saving the config
+async fn internal_dry_run_server_change(
+    state: &AppState,
+    name: String,
+    server_data: Option<McpServerEdit>,
+    is_new: bool,
+    custom_path: Option<String>,
+) -> Result<DryRunResult, String> {
+    let (mut config, _) = state.load_config(custom_path).await?;
+    let before = config
+        .mcp_servers
+        .get(&name)
+        .or(config.disabled_servers.get(&name))
+        .cloned();
+
+    // Same checks and mutation as the real commands, minus the keychain
+    let change = match server_data.map(normalize_server_edit).transpose() {
+        Ok(Some(server)) if is_new => ServerChange::Add {
+            server,
+            replace: false,
+        },
+        Ok(Some(server)) => ServerChange::Update(server),
+        Ok(None) => ServerChange::Delete,
+        Err(message) => {
+            return Ok(DryRunResult {
+                success: false,
+                message,
+                before,
+                after: None,
+            })
+        }
+    };
+    if let Err(message) = apply_server_change(&mut config, &name, change) {
+        return Ok(DryRunResult {
+            success: false,
+            message,
+            before,
+            after: None,
+        });
+    }
+
+    // Make sure the resulting config would serialize cleanly
+    serde_json::to_string_pretty(&config)
+        .map_err(|e| format!("Failed to serialize config: {}", e))?;
+
+    let after = config
+        .mcp_servers
+        .get(&name)
+        .or(config.disabled_servers.get(&name))
+        .cloned();
+    let action = match (&before, &after) {
+        (None, Some(_)) => "added",
+        (Some(_), Some(_)) => "updated",
+        _ => "deleted",
+    };
+
+    Ok(DryRunResult {
+        success: true,
+        message: format!("Server '{}' would be {} (dry run)", name, action),
+        before,
+        after,
+    })
+}
+
+// Copy the config to `{path}.backup` plus a timestamped `{path}.backup_{millis}` copy,
+// then prune the oldest timestamped backups beyond the retention count
+fn create_backup(config_path: &str, retention_count: usize) -> Result<(), String> {
+    let backup_path = format!("{}.backup", config_path);
+    retry_when_locked(|| fs::copy(config_path, &backup_path))
+        .map_err(|e| format!("Failed to create backup: {}", e))?;
+
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis();
+    let timestamped_path = format!("{}.backup_{}", config_path, timestamp);
+    retry_when_locked(|| fs::copy(config_path, &timestamped_path))
+        .map_err(|e| format!("Failed to create timestamped backup: {}", e))?;
+
+    prune_backups(config_path, retention_count);
+    Ok(())
+}
+
+// Write to a temp file next to `path` and rename it into place, so a crash mid-write
+// leaves either the old or the new config but never a truncated one. A rename across
+// filesystems can't work, so a failed rename falls back to copying over the target.
+fn write_config_atomically(path: &str, content: &str) -> std::io::Result<()> {
+    use std::io::Write;
+
+    let temp_path = format!("{}.tmp-{}", path, std::process::id());
+
+    let write_temp = || -> std::io::Result<()> {
+        let mut file = fs::File::create(&temp_path)?;
+        file.write_all(content.as_bytes())?;
+        file.sync_all()?;
+        // Keep the original file's permissions, e.g. a config made private by hand
+        if let Ok(metadata) = fs::metadata(path) {
+            fs::set_permissions(&temp_path, metadata.permissions())?;
+        }
+        Ok(())
+    };
+
+    let result = write_temp().and_then(|_| {
+        retry_when_locked(|| fs::rename(&temp_path, path))
+            .or_else(|_| retry_when_locked(|| fs::copy(&temp_path, path)).map(|_| ()))
+    });
+
+    // Already gone after a successful rename; otherwise don't leave it lying around
+    let _ = fs::remove_file(&temp_path);
+    result
+}
+
+// Antivirus scanners and Claude Desktop itself can hold the config open for a moment
+// on Windows. Retry such failures with backoff (50ms doubling, about 0.75s in total)
+// before giving up.
+const LOCKED_FILE_RETRIES: u32 = 4;
+const LOCKED_FILE_INITIAL_DELAY_MS: u64 = 50;
+
+fn retry_when_locked<T>(mut operation: impl FnMut() -> std::io::Result<T>) -> std::io::Result<T> {
+    let mut delay = std::time::Duration::from_millis(LOCKED_FILE_INITIAL_DELAY_MS);
+    let mut retries = 0;
+
+    loop {
+        match operation() {
+            Err(e) if retries < LOCKED_FILE_RETRIES && is_lock_error(&e) => {
+                std::thread::sleep(delay);
+                delay *= 2;
+                retries += 1;
             }
-        };
+            result => return result,
+        }
+    }
+}
 
-        if let Err(validation_error) = validate_claude_config_structure(&config) {
-            return Err(format!(
-                "Configuration validation failed: {}",
-                validation_error
-            ));
+fn is_lock_error(error: &std::io::Error) -> bool {
+    // ERROR_SHARING_VIOLATION and ERROR_LOCK_VIOLATION
+    const WINDOWS_LOCK_ERRORS: [i32; 2] = [32, 33];
+
+    matches!(
+        error.kind(),
+        std::io::ErrorKind::PermissionDenied | std::io::ErrorKind::WouldBlock
+    ) || (cfg!(windows)
+        && error
+            .raw_os_error()
+            .is_some_and(|code| WINDOWS_LOCK_ERRORS.contains(&code)))
+}
+
+// Only automatic `.backup_{ts}` copies count against the retention; manual backups were
+// made on purpose and are never pruned
+fn prune_backups(config_path: &str, retention_count: usize) {
+    let Some(file_name) = Path::new(config_path).file_name() else {
+        return;
+    };
+    let auto_prefix = format!("{}.backup_", file_name.to_string_lossy());
+
+    for (path, _) in collect_timestamped_backups(config_path)
+        .into_iter()
+        .filter(|(path, _)| {
+            path.file_name()
+                .is_some_and(|name| name.to_string_lossy().starts_with(&auto_prefix))
+        })
+        .skip(retention_count)
+    {
+        if let Err(e) = fs::remove_file(&path) {
+            tracing::warn!(error = %e, path = %path.display(), "Failed to remove old backup");
         }
+    }
+}
 
-        *self.config_cache.write().await = Some(config.clone());
-        Ok(config)
+// A retention of 0 would delete the timestamped copy every save just wrote
+fn check_backup_retention(count: usize) -> Result<(), String> {
+    if count == 0 {
+        Err("Backup retention count must be at least 1".to_string())
+    } else {
+        Ok(())
     }
+}
 
-    pub async fn save_config(&self, config: &ClaudeConfig) -> Result<(), String> {
-        let config_path = self.config_path.read().await.clone();
-        if config_path.is_empty() {
-            return Err("Config path not set".to_string());
-        }
+// Find `{path}.backup_{ts}` and `{path}.manual_backup_{ts}` files, sorted newest-first
+fn collect_timestamped_backups(
+    config_path: &str,
+) -> Vec<(std::path::PathBuf, std::time::SystemTime)> {
+    let config = Path::new(config_path);
+    let (Some(dir), Some(file_name)) = (config.parent(), config.file_name()) else {
+        return Vec::new();
+    };
+    let file_name = file_name.to_string_lossy();
+    let auto_prefix = format!("{}.backup_", file_name);
+    let manual_prefix = format!("{}.manual_backup_", file_name);
 
-        // Create backup
-        let backup_path = format!("{}.backup", config_path);
-        fs::copy(&config_path, &backup_path)
-            .map_err(|e| format!("Failed to create backup: {}", e))?;
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+
+    let mut backups: Vec<(std::path::PathBuf, std::time::SystemTime)> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            name.starts_with(&auto_prefix) || name.starts_with(&manual_prefix)
+        })
+        .map(|entry| {
+            let modified = entry
+                .metadata()
+                .and_then(|metadata| metadata.modified())
+                .unwrap_or(std::time::UNIX_EPOCH);
+            (entry.path(), modified)
+        })
+        .collect();
 
-        // Write updated config
-        let updated_content = serde_json::to_string_pretty(config)
-            .map_err(|e| format!("Failed to serialize config: {}", e))?;
+    backups.sort_by(|a, b| b.1.cmp(&a.1));
+    backups
+}
 
-        fs::write(&config_path, updated_content)
-            .map_err(|e| format!("Failed to write config: {}", e))?;
+// Move a server between `mcpServers` and `_disabledServers`
+async fn internal_set_server_enabled(
+    state: &AppState,
+    name: String,
+    enabled: bool,
+    app_handle: Option<&tauri::AppHandle>,
+) -> Result<SaveResult, String> {
+    if state.is_read_only().await {
+        return Ok(read_only_result());
+    }
 
-        // Update cache
-        *self.config_cache.write().await = Some(config.clone());
-        Ok(())
+    let _write_guard = state.lock_config_writes().await;
+    let (mut config, config_path) = state.load_config(None).await?;
+
+    let (from, to) = if enabled {
+        (&mut config.disabled_servers, &mut config.mcp_servers)
+    } else {
+        (&mut config.mcp_servers, &mut config.disabled_servers)
+    };
+
+    if to.contains_key(&name) {
+        return Ok(SaveResult {
+            success: false,
+            message: format!(
+                "Server '{}' is already {}",
+                name,
+                if enabled { "enabled" } else { "disabled" }
+            ),
+            path: None,
+        });
     }
 
-    pub async fn emit_event(
-        &self,
-        app_handle: &tauri::AppHandle,
-        event: &str,
-        payload: serde_json::Value,
-    ) {
-        if let Err(e) = app_handle.emit(event, payload) {
-            eprintln!("Failed to emit event {}: {}", event, e);
+    let server = match from.shift_remove(&name) {
+        Some(server) => server,
+        None => {
+            return Ok(SaveResult {
+                success: false,
+                message: format!("Server '{}' not found", name),
+                path: None,
+            });
         }
+    };
+    to.insert(name.clone(), server);
+
+    let operation = format!(
+        "{} server '{}'",
+        if enabled { "Enable" } else { "Disable" },
+        name
+    );
+    state.save_config(&config_path, &config, &operation).await?;
+
+    // Emit event for GUI updates
+    if let Some(handle) = app_handle {
+        state
+            .emit_event(
+                handle,
+                "config-changed",
+                ConfigChange::updated(&name).payload(),
+            )
+            .await;
     }
+
+    let action = if enabled { "enabled" } else { "disabled" };
+    Ok(SaveResult {
+        success: true,
+        message: format!("Server '{}' {} successfully", name, action),
+        path: Some(config_path.clone()),
+    })
 }
 
-// Internal shared functions that both Tauri commands and MCP tools can use
-async fn internal_parse_claude_json(
+async fn internal_compare_with_preset(
     state: &AppState,
+    name: String,
     custom_path: Option<String>,
-) -> Result<Vec<McpServerInfo>, String> {
-    let config = state.load_config(custom_path).await?;
+) -> Result<PresetComparison, String> {
+    let (config, _) = state.load_config(custom_path).await?;
+    let server = config
+        .mcp_servers
+        .get(&name)
+        .or_else(|| config.disabled_servers.get(&name))
+        .ok_or_else(|| format!("Server '{}' not found", name))?;
 
-    let mut servers = Vec::new();
-    for (name, server) in config.mcp_servers {
-        let env = server.env.unwrap_or_default();
-        servers.push(McpServerInfo {
+    let preset = match get_preset_server_by_name(name.clone()) {
+        Some(preset) => preset,
+        None => {
+            return Ok(PresetComparison {
+                message: format!("No preset matches server '{}'", name),
+                name,
+                preset_found: false,
+                matches_preset: false,
+                differences: Vec::new(),
+            })
+        }
+    };
+
+    let differences = diff_server_fields(server, &preset_as_server(&preset, server));
+    let message = if differences.is_empty() {
+        format!("Server '{}' matches its preset", name)
+    } else {
+        format!(
+            "Server '{}' differs from its preset in {} field(s)",
             name,
-            command: server.command,
-            args: server.args,
-            env,
-        });
+            differences.len()
+        )
+    };
+
+    Ok(PresetComparison {
+        name,
+        preset_found: true,
+        matches_preset: differences.is_empty(),
+        differences,
+        message,
+    })
+}
+
+async fn internal_get_preset_install_status(
+    state: &AppState,
+) -> Result<Vec<PresetInstallStatus>, String> {
+    let (config, _) = state.load_config(None).await?;
+
+    Ok(get_preset_servers_database()
+        .iter()
+        .map(|preset| {
+            let installed = config
+                .mcp_servers
+                .get(&preset.name)
+                .or_else(|| config.disabled_servers.get(&preset.name));
+
+            PresetInstallStatus {
+                name: preset.name.clone(),
+                installed: installed.is_some(),
+                installed_matches_preset: installed.is_some_and(|server| {
+                    diff_server_fields(server, &preset_as_server(preset, server))
+                        .iter()
+                        .all(|change| change.field.starts_with("env."))
+                }),
+            }
+        })
+        .collect())
+}
+
+// The server a preset would install, to diff against the installed `server`. Placeholder
+// defaults are filled in, and env values and cwd are taken from `server`, so only
+// missing or extra env keys count as differences.
+fn preset_as_server(preset: &PresetServer, server: &McpServer) -> McpServer {
+    let args = preset
+        .substitute_arg_placeholders(&HashMap::new())
+        .unwrap_or_else(|_| preset.args.clone());
+    let installed_env = server.env.clone().unwrap_or_default();
+    let env: HashMap<String, String> = preset
+        .env
+        .iter()
+        .flatten()
+        .map(|(key, _)| key.clone())
+        .chain(preset.api_keys.iter().map(|key| key.name.clone()))
+        .map(|key| {
+            let value = installed_env.get(&key).cloned().unwrap_or_default();
+            (key, value)
+        })
+        .collect();
+
+    McpServer {
+        command: preset.command.clone(),
+        args,
+        env: if env.is_empty() { None } else { Some(env) },
+        cwd: server.cwd.clone(),
     }
+}
 
-    // Sort servers alphabetically by name
-    servers.sort_by(|a, b| a.name.cmp(&b.name));
-    Ok(servers)
+// Group servers whose command, args and env are identical. Args are compared exactly,
+// order included, since reordering positional arguments changes their meaning.
+fn group_duplicate_servers(config: &ClaudeConfig) -> Vec<DuplicateGroup> {
+    let mut groups: HashMap<String, Vec<String>> = HashMap::new();
+
+    for (name, server) in &config.mcp_servers {
+        // Sort env so map ordering doesn't affect the comparison
+        let env: std::collections::BTreeMap<&String, &String> = server
+            .env
+            .as_ref()
+            .map(|env| env.iter().collect())
+            .unwrap_or_default();
+        let key = serde_json::json!([server.command, server.args, env]).to_string();
+        groups.entry(key).or_default().push(name.clone());
+    }
+
+    let mut duplicates: Vec<DuplicateGroup> = groups
+        .into_values()
+        .filter(|names| names.len() > 1)
+        .map(|mut names| {
+            names.sort();
+            DuplicateGroup {
+                command: config.mcp_servers[&names[0]].command.clone(),
+                servers: names,
+            }
+        })
+        .collect();
+
+    duplicates.sort_by(|a, b| a.servers[0].cmp(&b.servers[0]));
+    duplicates
 }
 
-async fn internal_add_server(
+#[derive(Debug, Serialize)]
+struct EnvUpdateResult {
+    success: bool,
+    message: String,
+    modified: Vec<String>,
+    not_found: Vec<String>,
+}
+
+// Set one env variable on several servers with a single load/backup/save cycle, e.g.
+// to rotate a shared token. Without `servers`, every server that already has the key
+// is updated.
+#[tauri::command]
+async fn set_env_across_servers(
+    state: tauri::State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+    key: String,
+    value: String,
+    servers: Option<Vec<String>>,
+) -> Result<EnvUpdateResult, String> {
+    let rejected = |message: String| EnvUpdateResult {
+        success: false,
+        message,
+        modified: Vec::new(),
+        not_found: Vec::new(),
+    };
+
+    if state.is_read_only().await {
+        return Ok(rejected(READ_ONLY_MESSAGE.to_string()));
+    }
+
+    let key = key.trim().to_string();
+    if !is_valid_env_key(&key) {
+        return Ok(rejected(format!(
+            "Invalid environment variable name '{}'",
+            key
+        )));
+    }
+
+    let _write_guard = state.lock_config_writes().await;
+    let (mut config, config_path) = state.load_config(None).await?;
+    let secrets_in_keychain = state.settings_cache.read().await.secrets_in_keychain;
+
+    let names: Vec<String> = match servers {
+        Some(names) => names,
+        None => config
+            .mcp_servers
+            .iter()
+            .chain(config.disabled_servers.iter())
+            .filter(|(_, server)| {
+                server
+                    .env
+                    .as_ref()
+                    .is_some_and(|env| env.contains_key(&key))
+            })
+            .map(|(name, _)| name.clone())
+            .collect(),
+    };
+
+    let mut modified = Vec::new();
+    let mut not_found = Vec::new();
+    for name in names {
+        let Some(server) = config
+            .mcp_servers
+            .get_mut(&name)
+            .or(config.disabled_servers.get_mut(&name))
+        else {
+            not_found.push(name);
+            continue;
+        };
+
+        let env = server.env.get_or_insert_with(HashMap::new);
+        if !secrets_in_keychain && env.get(&key) == Some(&value) {
+            continue;
+        }
+
+        // A keychain placeholder stays the same when the secret behind it changes, so
+        // keychain-backed servers always count as modified
+        let entry = HashMap::from([(key.clone(), value.clone())]);
+        let entry = if secrets_in_keychain {
+            move_secrets_to_keychain(&name, entry)?
+        } else {
+            entry
+        };
+        env.extend(entry);
+        modified.push(name);
+    }
+
+    if !modified.is_empty() {
+        let operation = format!("Set '{}' on {} servers", key, modified.len());
+        state.save_config(&config_path, &config, &operation).await?;
+        state
+            .emit_event(
+                &app_handle,
+                "config-changed",
+                ConfigChange {
+                    updated: modified.clone(),
+                    ..ConfigChange::default()
+                }
+                .payload(),
+            )
+            .await;
+    }
+
+    Ok(EnvUpdateResult {
+        success: true,
+        message: format!("Updated '{}' on {} server(s)", key, modified.len()),
+        modified,
+        not_found,
+    })
+}
+
+// Delete several servers with a single load/backup/save cycle
+async fn internal_delete_servers(
+    state: &AppState,
+    names: Vec<String>,
+    app_handle: Option<&tauri::AppHandle>,
+) -> Result<Vec<BulkDeleteResult>, String> {
+    // Like read_only_result() for delete_server, but per name
+    if state.is_read_only().await {
+        return Ok(names
+            .into_iter()
+            .map(|name| BulkDeleteResult {
+                name,
+                status: "read_only".to_string(),
+            })
+            .collect());
+    }
+
+    let _write_guard = state.lock_config_writes().await;
+    let (config, config_path) = state.load_config(None).await?;
+    remove_servers(state, config, &config_path, names, app_handle).await
+}
+
+// Remove `names` from a config loaded under the write lock, which the caller still holds,
+// and save it once
+async fn remove_servers(
+    state: &AppState,
+    mut config: ClaudeConfig,
+    config_path: &str,
+    names: Vec<String>,
+    app_handle: Option<&tauri::AppHandle>,
+) -> Result<Vec<BulkDeleteResult>, String> {
+    let results: Vec<BulkDeleteResult> = names
+        .into_iter()
+        .map(|name| {
+            let status = if config.mcp_servers.shift_remove(&name).is_some()
+                || config.disabled_servers.shift_remove(&name).is_some()
+            {
+                "deleted"
+            } else {
+                "not_found"
+            };
+            BulkDeleteResult {
+                name,
+                status: status.to_string(),
+            }
+        })
+        .collect();
+
+    let deleted: Vec<&String> = results
+        .iter()
+        .filter(|result| result.status == "deleted")
+        .map(|result| &result.name)
+        .collect();
+
+    if deleted.is_empty() {
+        return Ok(results);
+    }
+
+    state
+        .save_config(config_path, &config, "Delete multiple servers")
+        .await?;
+    for name in &deleted {
+        remove_server_metadata(config_path, name);
+    }
+
+    // Emit a single event for GUI updates
+    if let Some(handle) = app_handle {
+        state
+            .emit_event(
+                handle,
+                "config-changed",
+                ConfigChange {
+                    removed: deleted.iter().map(|name| name.to_string()).collect(),
+                    ..ConfigChange::default()
+                }
+                .payload(),
+            )
+            .await;
+    }
+
+    Ok(results)
+}
+
+// A single-argument change to a server's args
+pub enum ArgEdit {
+    Append(String),
+    // By index, or the first arg equal to the value
+    Remove {
+        index: Option<usize>,
+        value: Option<String>,
+    },
+    Set {
+        index: usize,
+        value: String,
+    },
+}
+
+#[derive(Debug, Serialize)]
+pub struct ArgEditResult {
+    pub success: bool,
+    pub message: String,
+    // The server's args after the edit (unchanged on failure)
+    pub args: Vec<String>,
+}
+
+async fn internal_edit_server_arg(
     state: &AppState,
     name: String,
-    server_data: McpServerEdit,
+    edit: ArgEdit,
+    app_handle: Option<&tauri::AppHandle>,
+) -> Result<ArgEditResult, String> {
+    if state.is_read_only().await {
+        return Ok(ArgEditResult {
+            success: false,
+            message: READ_ONLY_MESSAGE.to_string(),
+            args: Vec::new(),
+        });
+    }
+
+    let _write_guard = state.lock_config_writes().await;
+    let (mut config, config_path) = state.load_config(None).await?;
+
+    let Some(server) = config
+        .mcp_servers
+        .get_mut(&name)
+        .or(config.disabled_servers.get_mut(&name))
+    else {
+        return Ok(ArgEditResult {
+            success: false,
+            message: format!("Server '{}' not found", name),
+            args: Vec::new(),
+        });
+    };
+
+    let arg_count = server.args.len();
+    let failure = |message: String, args: &[String]| ArgEditResult {
+        success: false,
+        message,
+        args: args.to_vec(),
+    };
+
+    let message = match edit {
+        ArgEdit::Append(value) => {
+            server.args.push(value.clone());
+            format!("Appended '{}'", value)
+        }
+        ArgEdit::Remove {
+            index: Some(index),
+            value: None,
+        } => {
+            if index >= arg_count {
+                return Ok(failure(
+                    format!("Index {} is out of range ({} args)", index, arg_count),
+                    &server.args,
+                ));
+            }
+            let removed = server.args.remove(index);
+            format!("Removed '{}' at index {}", removed, index)
+        }
+        ArgEdit::Remove {
+            index: None,
+            value: Some(value),
+        } => match server.args.iter().position(|arg| *arg == value) {
+            Some(index) => {
+                server.args.remove(index);
+                format!("Removed '{}' at index {}", value, index)
+            }
+            None => {
+                return Ok(failure(
+                    format!("Server '{}' has no arg '{}'", name, value),
+                    &server.args,
+                ))
+            }
+        },
+        ArgEdit::Remove { .. } => {
+            return Ok(failure(
+                "Specify exactly one of index or value".to_string(),
+                &server.args,
+            ))
+        }
+        ArgEdit::Set { index, value } => {
+            if index >= arg_count {
+                return Ok(failure(
+                    format!("Index {} is out of range ({} args)", index, arg_count),
+                    &server.args,
+                ));
+            }
+            let previous = std::mem::replace(&mut server.args[index], value.clone());
+            format!(
+                "Replaced '{}' with '{}' at index {}",
+                previous, value, index
+            )
+        }
+    };
+    let args = server.args.clone();
+
+    state
+        .save_config(
+            &config_path,
+            &config,
+            &format!("Edit args of server '{}'", name),
+        )
+        .await?;
+    record_server_metadata(&config_path, &name);
+
+    // Emit event for GUI updates
+    if let Some(handle) = app_handle {
+        state
+            .emit_event(
+                handle,
+                "server-updated",
+                serde_json::json!({ "name": name }),
+            )
+            .await;
+        state
+            .emit_event(
+                handle,
+                "config-changed",
+                ConfigChange::updated(&name).payload(),
+            )
+            .await;
+    }
+
+    Ok(ArgEditResult {
+        success: true,
+        message,
+        args,
+    })
+}
+
+// Rename within a single load/save so there is one backup and no window where both or
+// neither name exists. The server keeps its position and enabled state.
+async fn internal_rename_server(
+    state: &AppState,
+    old_name: String,
+    new_name: String,
     app_handle: Option<&tauri::AppHandle>,
 ) -> Result<SaveResult, String> {
-    let mut config = state.load_config(None).await?;
+    if state.is_read_only().await {
+        return Ok(read_only_result());
+    }
+
+    let new_name = new_name.trim().to_string();
+    if new_name.is_empty() {
+        return Ok(SaveResult {
+            success: false,
+            message: "New server name cannot be empty".to_string(),
+            path: None,
+        });
+    }
+
+    let _write_guard = state.lock_config_writes().await;
+    let (mut config, config_path) = state.load_config(None).await?;
 
-    if config.mcp_servers.contains_key(&name) {
+    if config.mcp_servers.contains_key(&new_name) || config.disabled_servers.contains_key(&new_name)
+    {
         return Ok(SaveResult {
             success: false,
-            message: format!("Server '{}' already exists", name),
+            message: format!("Server '{}' already exists", new_name),
+            path: None,
         });
     }
 
-    let env = if server_data.env.is_empty() {
-        None
+    let servers = if config.mcp_servers.contains_key(&old_name) {
+        &mut config.mcp_servers
+    } else if config.disabled_servers.contains_key(&old_name) {
+        &mut config.disabled_servers
     } else {
-        Some(server_data.env)
+        return Ok(SaveResult {
+            success: false,
+            message: format!("Server '{}' not found", old_name),
+            path: None,
+        });
     };
 
-    config.mcp_servers.insert(
-        name.clone(),
-        McpServer {
-            command: server_data.command,
-            args: server_data.args,
-            env,
-        },
-    );
+    if let Some((index, _, server)) = servers.shift_remove_full(&old_name) {
+        servers.shift_insert(index, new_name.clone(), server);
+    }
 
-    state.save_config(&config).await?;
+    let operation = format!("Rename server '{}' to '{}'", old_name, new_name);
+    state.save_config(&config_path, &config, &operation).await?;
+    rename_server_metadata(&config_path, &old_name, &new_name);
 
     // Emit event for GUI updates
     if let Some(handle) = app_handle {
         state
-            .emit_event(handle, "server-added", serde_json::json!({ "name": name }))
+            .emit_event(
+                handle,
+                "server-renamed",
+                serde_json::json!({ "old_name": old_name, "new_name": new_name }),
+            )
             .await;
         state
-            .emit_event(handle, "config-changed", serde_json::json!({}))
+            .emit_event(
+                handle,
+                "config-changed",
+                ConfigChange {
+                    added: vec![new_name.clone()],
+                    removed: vec![old_name.clone()],
+                    ..ConfigChange::default()
+                }
+                .payload(),
+            )
             .await;
     }
 
     Ok(SaveResult {
         success: true,
-        message: format!("Server '{}' added successfully", name),
+        message: format!(
+            "Server '{}' renamed to '{}' successfully",
+            old_name, new_name
+        ),
+        path: Some(config_path),
     })
 }
 
-async fn internal_delete_server(
+async fn internal_duplicate_server(
     state: &AppState,
-    name: String,
+    source_name: String,
+    new_name: String,
     app_handle: Option<&tauri::AppHandle>,
 ) -> Result<SaveResult, String> {
-    let mut config = state.load_config(None).await?;
+    if state.is_read_only().await {
+        return Ok(read_only_result());
+    }
 
-    if config.mcp_servers.remove(&name).is_none() {
+    let new_name = new_name.trim().to_string();
+    if new_name.is_empty() {
         return Ok(SaveResult {
             success: false,
-            message: format!("Server '{}' not found", name),
+            message: "New server name cannot be empty".to_string(),
+            path: None,
+        });
+    }
+
+    let _write_guard = state.lock_config_writes().await;
+    let (mut config, config_path) = state.load_config(None).await?;
+
+    if config.mcp_servers.contains_key(&new_name) || config.disabled_servers.contains_key(&new_name)
+    {
+        return Ok(SaveResult {
+            success: false,
+            message: format!("Server '{}' already exists", new_name),
+            path: None,
         });
     }
 
-    state.save_config(&config).await?;
+    // The copy lands next to its source, so duplicating a disabled server gives a
+    // disabled copy
+    let servers = if config.mcp_servers.contains_key(&source_name) {
+        &mut config.mcp_servers
+    } else if config.disabled_servers.contains_key(&source_name) {
+        &mut config.disabled_servers
+    } else {
+        return Ok(SaveResult {
+            success: false,
+            message: format!("Server '{}' not found", source_name),
+            path: None,
+        });
+    };
+
+    let source = servers[&source_name].clone();
+    servers.insert(new_name.clone(), source);
+
+    let operation = format!("Duplicate server '{}' as '{}'", source_name, new_name);
+    state.save_config(&config_path, &config, &operation).await?;
+    record_server_metadata(&config_path, &new_name);
 
     // Emit event for GUI updates
     if let Some(handle) = app_handle {
         state
             .emit_event(
                 handle,
-                "server-deleted",
-                serde_json::json!({ "name": name }),
+                "server-added",
+                serde_json::json!({ "name": new_name }),
             )
             .await;
         state
-            .emit_event(handle, "config-changed", serde_json::json!({}))
+            .emit_event(
+                handle,
+                "config-changed",
+                ConfigChange::added(&new_name).payload(),
+            )
             .await;
     }
 
     Ok(SaveResult {
         success: true,
-        message: format!("Server '{}' deleted successfully", name),
+        message: format!(
+            "Server '{}' duplicated as '{}' successfully",
+            source_name, new_name
+        ),
+        path: Some(config_path.clone()),
     })
 }
 
+async fn internal_import_servers_from_json(
+    state: &AppState,
+    json: String,
+    conflict_strategy: ConflictStrategy,
+    app_handle: Option<&tauri::AppHandle>,
+) -> Result<ImportSummary, String> {
+    let imported: ClaudeConfig = match serde_json::from_str(&json) {
+        Ok(imported) => imported,
+        Err(e) => {
+            let error_info = analyze_json_error(&json, &e);
+            let error_json = serde_json::to_string(&error_info).unwrap_or_else(|_| {
+                format!(
+                    "{{\"error_type\":\"unknown\",\"message\":\"Failed to parse JSON: {}\"}}",
+                    e
+                )
+            });
+            return Err(format!("JSON_ERROR:{}", error_json));
+        }
+    };
+
+    let _write_guard = state.lock_config_writes().await;
+    let (mut config, config_path) = state.load_config(None).await?;
+    let previous = config.clone();
+
+    let mut summary = ImportSummary {
+        added: 0,
+        skipped: 0,
+        renamed: 0,
+        overwritten: 0,
+        invalid: Vec::new(),
+        message: String::new(),
+    };
+
+    // Disabled servers are imported too and stay disabled. Sort for a deterministic
+    // rename order.
+    let mut incoming: Vec<(String, McpServer, bool)> = imported
+        .mcp_servers
+        .into_iter()
+        .map(|(name, server)| (name, server, true))
+        .chain(
+            imported
+                .disabled_servers
+                .into_iter()
+                .map(|(name, server)| (name, server, false)),
+        )
+        .collect();
+    incoming.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let exists = |config: &ClaudeConfig, name: &str| {
+        config.mcp_servers.contains_key(name) || config.disabled_servers.contains_key(name)
+    };
+    let secrets_in_keychain = state.settings_cache.read().await.secrets_in_keychain;
+    let mut written = Vec::new();
+
+    for (name, server, enabled) in incoming {
+        let server_data = McpServerEdit {
+            command: server.command,
+            args: server.args,
+            env: server.env.unwrap_or_default(),
+            cwd: server.cwd,
+        };
+        let server = match normalize_server_edit(server_data) {
+            Ok(server) => server,
+            Err(message) => {
+                summary.invalid.push(format!("{}: {}", name, message));
+                continue;
+            }
+        };
+
+        let name = if !exists(&config, &name) {
+            summary.added += 1;
+            name
+        } else {
+            match conflict_strategy {
+                ConflictStrategy::Skip => {
+                    summary.skipped += 1;
+                    continue;
+                }
+                ConflictStrategy::Overwrite => {
+                    // Replaced in place when the enabled state matches
+                    if enabled {
+                        config.disabled_servers.shift_remove(&name);
+                    } else {
+                        config.mcp_servers.shift_remove(&name);
+                    }
+                    summary.overwritten += 1;
+                    name
+                }
+                ConflictStrategy::Rename => {
+                    let mut suffix = 2;
+                    let mut new_name = format!("{}-{}", name, suffix);
+                    while exists(&config, &new_name) {
+                        suffix += 1;
+                        new_name = format!("{}-{}", name, suffix);
+                    }
+                    summary.renamed += 1;
+                    new_name
+                }
+            }
+        };
+
+        let servers = if enabled {
+            &mut config.mcp_servers
+        } else {
+            &mut config.disabled_servers
+        };
+        servers.insert(name.clone(), server);
+        if secrets_in_keychain {
+            move_server_secrets_to_keychain(&mut config, &name)?;
+        }
+        written.push(name);
+    }
+
+    if !written.is_empty() {
+        state
+            .save_config(&config_path, &config, "Import servers")
+            .await?;
+        for name in &written {
+            record_server_metadata(&config_path, name);
+        }
+
+        // Emit event for GUI updates
+        if let Some(handle) = app_handle {
+            state
+                .emit_event(
+                    handle,
+                    "config-changed",
+                    ConfigChange::between(&previous, &config).payload(),
+                )
+                .await;
+        }
+    }
+
+    summary.message = format!(
+        "Imported servers: {} added, {} overwritten, {} renamed, {} skipped, {} invalid",
+        summary.added,
+        summary.overwritten,
+        summary.renamed,
+        summary.skipped,
+        summary.invalid.len()
+    );
+    Ok(summary)
+}
+
+// Rank presets (and optionally installed server names) against a fuzzy query, best match first
+fn rank_search_results(query: &str, installed_names: &[String]) -> Vec<SearchResult> {
+    let mut results: Vec<SearchResult> = get_preset_servers_database()
+        .into_iter()
+        .filter_map(|preset| {
+            // Name matches count most, then category, then description
+            let score = fuzzy_score(query, &preset.name)
+                .max(fuzzy_score(query, &preset.category) * 0.7)
+                .max(fuzzy_score(query, &preset.description) * 0.5);
+            (score > 0.0).then(|| SearchResult {
+                name: preset.name,
+                source: "preset".to_string(),
+                category: Some(preset.category),
+                description: Some(preset.description),
+                score,
+            })
+        })
+        .collect();
+
+    results.extend(installed_names.iter().filter_map(|name| {
+        let score = fuzzy_score(query, name);
+        (score > 0.0).then(|| SearchResult {
+            name: name.clone(),
+            source: "installed".to_string(),
+            category: None,
+            description: None,
+            score,
+        })
+    }));
+
+    results.sort_by(|a, b| {
+        b.score
+            .partial_cmp(&a.score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    results
+}
+
+// Score in 0..=1: exact > prefix > substring > subsequence ("brv" in "brave-search") > close typo
+fn fuzzy_score(query: &str, text: &str) -> f64 {
+    let query = query.trim().to_lowercase();
+    let text = text.to_lowercase();
+    if query.is_empty() || text.is_empty() {
+        return 0.0;
+    }
+
+    if text == query {
+        return 1.0;
+    }
+    if text.starts_with(&query) {
+        return 0.9;
+    }
+    if text.contains(&query) {
+        return 0.8;
+    }
+    if is_subsequence(&query, &text) {
+        let coverage = query.chars().count() as f64 / text.chars().count() as f64;
+        return 0.5 + 0.3 * coverage;
+    }
+
+    // Fall back to typo tolerance against individual words
+    let best_similarity = text
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let max_len = word.chars().count().max(query.chars().count()) as f64;
+            1.0 - levenshtein(&query, word) as f64 / max_len
+        })
+        .fold(0.0, f64::max);
+
+    if best_similarity >= 0.6 {
+        best_similarity * 0.6
+    } else {
+        0.0
+    }
+}
+
+fn is_subsequence(needle: &str, haystack: &str) -> bool {
+    let mut haystack_chars = haystack.chars();
+    needle.chars().all(|c| haystack_chars.any(|h| h == c))
+}
+
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b_chars: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b_chars.len()).collect();
+
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b_chars.len() + 1];
+        for (j, b_char) in b_chars.iter().enumerate() {
+            let cost = if a_char == *b_char { 0 } else { 1 };
+            current[j + 1] = (previous[j] + cost)
+                .min(previous[j + 1] + 1)
+                .min(current[j] + 1);
+        }
+        previous = current;
+    }
+
+    previous[b_chars.len()]
+}
+
+// User-defined presets live in `{settings_dir}/custom_presets.json` as a JSON array
+fn custom_presets_path() -> Result<std::path::PathBuf, String> {
+    Ok(settings_dir()?.join("custom_presets.json"))
+}
+
+fn load_custom_presets() -> Result<Vec<PresetServer>, String> {
+    let path = custom_presets_path()?;
+    match fs::read_to_string(&path) {
+        Ok(content) => serde_json::from_str::<Vec<PresetServer>>(&content)
+            .map(|presets| presets.into_iter().map(normalize_preset).collect())
+            .map_err(|e| format!("Failed to parse {}: {}", path.display(), e)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(format!("Failed to read {}: {}", path.display(), e)),
+    }
+}
+
+// Custom presets written before `apiKeys` existed only set `apiKeyName` and
+// `apiKeyDescription`; turn those into the equivalent `apiKeys` entry
+fn normalize_preset(mut preset: PresetServer) -> PresetServer {
+    if preset.api_keys.is_empty() {
+        if let Some(name) = preset
+            .api_key_name
+            .clone()
+            .filter(|name| !name.trim().is_empty())
+        {
+            preset.api_keys.push(ApiKeyRequirement {
+                name,
+                description: preset.api_key_description.clone().unwrap_or_default(),
+                required: preset.requires_api_key,
+            });
+        }
+    }
+    preset
+}
+
+fn save_custom_presets(presets: &[PresetServer]) -> Result<(), String> {
+    let path = custom_presets_path()?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create settings directory: {}", e))?;
+    }
+
+    let content = serde_json::to_string_pretty(presets)
+        .map_err(|e| format!("Failed to serialize custom presets: {}", e))?;
+    fs::write(&path, content).map_err(|e| format!("Failed to write custom presets: {}", e))
+}
+
+// The preset catalog as pretty JSON in the custom_presets.json shape, built-ins only
+// unless `include_custom` is set
+#[tauri::command]
+fn export_preset_database(include_custom: Option<bool>) -> Result<String, String> {
+    let presets = if include_custom.unwrap_or(false) {
+        get_preset_servers_database()
+    } else {
+        builtin_preset_servers()
+    };
+
+    let json = serde_json::to_string_pretty(&presets)
+        .map_err(|e| format!("Failed to serialize presets: {}", e))?;
+
+    // Custom presets are read back with the same deserializer, so make sure it accepts this
+    serde_json::from_str::<Vec<PresetServer>>(&json)
+        .map_err(|e| format!("Exported presets do not round-trip: {}", e))?;
+
+    Ok(json)
+}
+
+// Built-in presets merged with the user's custom ones, which override built-ins by name.
+// A broken custom presets file is reported and ignored so the built-ins stay usable.
 fn get_preset_servers_database() -> Vec<PresetServer> {
+    let mut presets = builtin_preset_servers();
+
+    let custom_presets = load_custom_presets().unwrap_or_else(|e| {
+        tracing::warn!("{}", e);
+        Vec::new()
+    });
+    for custom in custom_presets {
+        match presets.iter_mut().find(|preset| preset.name == custom.name) {
+            Some(preset) => *preset = custom,
+            None => presets.push(custom),
+        }
+    }
+
+    presets
+}
+
+fn builtin_preset_servers() -> Vec<PresetServer> {
     vec![
         PresetServer {
             name: "dice".to_string(),
@@ -905,6 +6326,7 @@ fn get_preset_servers_database() -> Vec<PresetServer> {
             requires_api_key: false,
             api_key_name: None,
             api_key_description: None,
+            arg_placeholders: vec![],
         },
         PresetServer {
             name: "time".to_string(),
@@ -915,13 +6337,18 @@ fn get_preset_servers_database() -> Vec<PresetServer> {
             command: "uvx".to_string(),
             args: vec![
                 "mcp-server-time".to_string(),
-                "--local-timezone=UTC".to_string(),
+                "--local-timezone={{timezone}}".to_string(),
             ],
             env: None,
             api_keys: vec![],
             requires_api_key: false,
             api_key_name: None,
             api_key_description: None,
+            arg_placeholders: vec![ArgPlaceholder {
+                token: "timezone".to_string(),
+                description: "IANA timezone name, e.g. Europe/Amsterdam".to_string(),
+                default: Some("UTC".to_string()),
+            }],
         },
         PresetServer {
             name: "sequential-thinking".to_string(),
@@ -940,6 +6367,7 @@ fn get_preset_servers_database() -> Vec<PresetServer> {
             requires_api_key: false,
             api_key_name: None,
             api_key_description: None,
+            arg_placeholders: vec![],
         },
         PresetServer {
             name: "browsermcp".to_string(),
@@ -954,6 +6382,7 @@ fn get_preset_servers_database() -> Vec<PresetServer> {
             requires_api_key: false,
             api_key_name: None,
             api_key_description: None,
+            arg_placeholders: vec![],
         },
         PresetServer {
             name: "brave-search".to_string(),
@@ -976,6 +6405,7 @@ fn get_preset_servers_database() -> Vec<PresetServer> {
             api_key_description: Some(
                 "Get your API key from https://brave.com/search/api/".to_string(),
             ),
+            arg_placeholders: vec![],
         },
         PresetServer {
             name: "openweather".to_string(),
@@ -1002,6 +6432,7 @@ fn get_preset_servers_database() -> Vec<PresetServer> {
             api_key_description: Some(
                 "Get your API key from https://openweathermap.org/api".to_string(),
             ),
+            arg_placeholders: vec![],
         },
         PresetServer {
             name: "context7".to_string(),
@@ -1015,6 +6446,7 @@ fn get_preset_servers_database() -> Vec<PresetServer> {
             requires_api_key: false,
             api_key_name: None,
             api_key_description: None,
+            arg_placeholders: vec![],
         },
         PresetServer {
             name: "docker".to_string(),
@@ -1032,6 +6464,7 @@ fn get_preset_servers_database() -> Vec<PresetServer> {
             requires_api_key: false,
             api_key_name: None,
             api_key_description: None,
+            arg_placeholders: vec![],
         },
         PresetServer {
             name: "desktop-commander".to_string(),
@@ -1050,9 +6483,10 @@ fn get_preset_servers_database() -> Vec<PresetServer> {
             requires_api_key: false,
             api_key_name: None,
             api_key_description: None,
+            arg_placeholders: vec![],
         },
         PresetServer {
-            name: "mcp-manager".to_string(),
+            name: SELF_PRESET_NAME.to_string(),
             description: "control mcp manager using ai".to_string(),
             category: "Development".to_string(),
             server_type: ServerType::Npx,
@@ -1068,11 +6502,12 @@ fn get_preset_servers_database() -> Vec<PresetServer> {
             requires_api_key: false,
             api_key_name: None,
             api_key_description: None,
+            arg_placeholders: vec![],
         },
     ]
 }
 
-fn analyze_json_error(_json_content: &str, error: &serde_json::Error) -> JsonErrorInfo {
+fn analyze_json_error(json_content: &str, error: &serde_json::Error) -> JsonErrorInfo {
     let error_msg = error.to_string();
     let line = error.line();
     let column = error.column();
@@ -1119,158 +6554,607 @@ fn analyze_json_error(_json_content: &str, error: &serde_json::Error) -> JsonErr
         column: Some(column),
         suggestion,
         has_backup: false, // Will be updated by caller
+        context_lines: error_context_lines(json_content, line, column),
+    }
+}
+
+// Render lines around a 1-based line/column as `  12 | ...` with a caret line below the
+// offending one. Positions past the end (e.g. EOF errors) are clamped to the last line.
+fn error_context_lines(content: &str, line: usize, column: usize) -> Vec<String> {
+    let lines: Vec<&str> = content.lines().collect();
+    if lines.is_empty() {
+        return Vec::new();
+    }
+
+    let target = line.clamp(1, lines.len()) - 1;
+    let first = target.saturating_sub(1);
+    let last = (target + 1).min(lines.len() - 1);
+    let gutter_width = (last + 1).to_string().len();
+
+    let mut context = Vec::new();
+    for (index, text) in lines.iter().enumerate().take(last + 1).skip(first) {
+        context.push(format!(
+            "{:>width$} | {}",
+            index + 1,
+            text,
+            width = gutter_width
+        ));
+        if index == target {
+            let line_len = text.chars().count();
+            let caret_offset = column.clamp(1, line_len + 1) - 1;
+            context.push(format!(
+                "{:>width$} | {}^",
+                "",
+                " ".repeat(caret_offset),
+                width = gutter_width
+            ));
+        }
+    }
+    context
+}
+
+const CLAUDE_CONFIG_SCHEMA: &str = include_str!("claude_config.schema.json");
+
+fn config_schema_validator() -> Result<&'static jsonschema::Validator, String> {
+    static VALIDATOR: std::sync::OnceLock<Result<jsonschema::Validator, String>> =
+        std::sync::OnceLock::new();
+
+    VALIDATOR
+        .get_or_init(|| {
+            let schema: serde_json::Value = serde_json::from_str(CLAUDE_CONFIG_SCHEMA)
+                .map_err(|e| format!("Invalid embedded config schema: {}", e))?;
+            jsonschema::validator_for(&schema)
+                .map_err(|e| format!("Invalid embedded config schema: {}", e))
+        })
+        .as_ref()
+        .map_err(|e| e.clone())
+}
+
+fn validate_against_schema(
+    raw_config: &serde_json::Value,
+) -> Result<Vec<SchemaValidationError>, String> {
+    let validator = config_schema_validator()?;
+
+    Ok(validator
+        .iter_errors(raw_config)
+        .map(|error| {
+            let path = error.instance_path.to_string();
+            SchemaValidationError {
+                path: if path.is_empty() {
+                    "/".to_string()
+                } else {
+                    path
+                },
+                message: error.to_string(),
+            }
+        })
+        .collect())
+}
+
+fn validate_claude_config_structure(config: &ClaudeConfig) -> Result<(), String> {
+    // Check if mcpServers exists and is valid
+    if config.mcp_servers.is_empty() {
+        return Ok(()); // Empty config is valid
+    }
+
+    // Validate each server configuration
+    for (name, server) in &config.mcp_servers {
+        if name.trim().is_empty() {
+            return Err("Server name cannot be empty".to_string());
+        }
+
+        if server.command.trim().is_empty() {
+            return Err(format!("Server '{}' has an empty command", name));
+        }
+
+        // Check for common command issues
+        if server.command.contains(" ") && !server.command.starts_with("\"") {
+            return Err(format!("Server '{}' command contains spaces but is not quoted. Consider moving arguments to the 'args' array", name));
+        }
+    }
+
+    Ok(())
+}
+
+// Config text ready for serde_json: a leading UTF-8 BOM (common from Windows editors)
+// is dropped and a blank file reads as a config with no servers
+fn normalize_config_content(content: &str) -> &str {
+    let content = content.strip_prefix('\u{feff}').unwrap_or(content);
+    if content.trim().is_empty() {
+        r#"{"mcpServers": {}}"#
+    } else {
+        content
+    }
+}
+
+// Serialize the config on top of the file's current JSON so that keys we don't model
+// (e.g. `globalShortcut`) survive, keeping their original order
+fn serialize_config_preserving_keys(
+    config_path: &str,
+    config: &ClaudeConfig,
+) -> Result<String, String> {
+    let mut document = fs::read_to_string(config_path)
+        .ok()
+        .and_then(|content| {
+            serde_json::from_str::<serde_json::Value>(normalize_config_content(&content)).ok()
+        })
+        .filter(|value| value.is_object())
+        .unwrap_or_else(|| serde_json::json!({}));
+
+    let servers = serde_json::to_value(&config.mcp_servers)
+        .map_err(|e| format!("Failed to serialize config: {}", e))?;
+    let disabled_servers = serde_json::to_value(&config.disabled_servers)
+        .map_err(|e| format!("Failed to serialize config: {}", e))?;
+
+    if let Some(object) = document.as_object_mut() {
+        object.insert("mcpServers".to_string(), servers);
+        if config.disabled_servers.is_empty() {
+            object.shift_remove("_disabledServers");
+        } else {
+            object.insert("_disabledServers".to_string(), disabled_servers);
+        }
+    }
+
+    serde_json::to_string_pretty(&document)
+        .map_err(|e| format!("Failed to serialize config: {}", e))
+}
+
+// Per-user application data directory that both Claude and mcp-manager keep their files in
+fn app_data_dir() -> Result<std::path::PathBuf, String> {
+    #[cfg(target_os = "windows")]
+    {
+        let appdata =
+            env::var("APPDATA").map_err(|_| "Could not determine APPDATA directory".to_string())?;
+        Ok(std::path::PathBuf::from(appdata))
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        let home_dir =
+            env::var("HOME").map_err(|_| "Could not determine home directory".to_string())?;
+        Ok(Path::new(&home_dir).join("Library/Application Support"))
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        let home_dir =
+            env::var("HOME").map_err(|_| "Could not determine home directory".to_string())?;
+        Ok(Path::new(&home_dir).join(".config"))
+    }
+
+    #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+    {
+        Err("Unsupported operating system".to_string())
+    }
+}
+
+fn get_claude_config_path() -> Result<String, String> {
+    Ok(app_data_dir()?
+        .join("Claude")
+        .join("claude_desktop_config.json")
+        .to_string_lossy()
+        .to_string())
+}
+
+// Secrets stored in the OS keychain are referenced from the config as `${keychain:<server>/<key>}`
+const KEYCHAIN_SERVICE: &str = "mcp-manager";
+
+fn store_secret(server_name: &str, key: &str, value: &str) -> Result<String, String> {
+    let account = format!("{}/{}", server_name, key);
+    let entry = keyring::Entry::new(KEYCHAIN_SERVICE, &account)
+        .map_err(|e| format!("Failed to access keychain: {}", e))?;
+    entry
+        .set_password(value)
+        .map_err(|e| format!("Failed to store secret '{}' in keychain: {}", key, e))?;
+
+    Ok(format!("${{keychain:{}}}", account))
+}
+
+fn retrieve_secret(account: &str) -> Result<String, String> {
+    let entry = keyring::Entry::new(KEYCHAIN_SERVICE, account)
+        .map_err(|e| format!("Failed to access keychain: {}", e))?;
+    entry
+        .get_password()
+        .map_err(|e| format!("Failed to read secret '{}' from keychain: {}", account, e))
+}
+
+fn parse_keychain_placeholder(value: &str) -> Option<&str> {
+    value.strip_prefix("${keychain:")?.strip_suffix('}')
+}
+
+// Trim env keys and require them to look like `[A-Za-z_][A-Za-z0-9_]*`, listing every bad
+// key in the error so it can be fixed in one go
+fn normalize_env_keys(env: HashMap<String, String>) -> Result<HashMap<String, String>, String> {
+    let mut normalized = HashMap::new();
+    let mut invalid = Vec::new();
+
+    for (key, value) in env {
+        let trimmed = key.trim();
+        if !is_valid_env_key(trimmed) || normalized.contains_key(trimmed) {
+            invalid.push(format!("'{}'", key));
+        } else {
+            normalized.insert(trimmed.to_string(), value);
+        }
+    }
+
+    if invalid.is_empty() {
+        Ok(normalized)
+    } else {
+        invalid.sort();
+        Err(format!(
+            "Invalid environment variable names: {}. Names must start with a letter or '_' and contain only letters, digits and '_', with no duplicates",
+            invalid.join(", ")
+        ))
+    }
+}
+
+// Trim the working directory, treating a blank one as unset, and require it to be an
+// existing directory
+fn normalize_cwd(cwd: Option<String>) -> Result<Option<String>, String> {
+    let Some(cwd) = cwd
+        .map(|cwd| cwd.trim().to_string())
+        .filter(|cwd| !cwd.is_empty())
+    else {
+        return Ok(None);
+    };
+
+    if Path::new(&cwd).is_dir() {
+        Ok(Some(cwd))
+    } else {
+        Err(format!("Working directory '{}' does not exist", cwd))
     }
 }
 
-fn validate_claude_config_structure(config: &ClaudeConfig) -> Result<(), String> {
-    // Check if mcpServers exists and is valid
-    if config.mcp_servers.is_empty() {
-        return Ok(()); // Empty config is valid
-    }
+// `KEY=VALUE` lines from a `.env` file. Blank lines and `#` comments are skipped, an
+// `export ` prefix is allowed, and values may be single or double quoted. Later lines
+// win when a key repeats.
+fn parse_env_file(content: &str) -> Result<Vec<(String, String)>, String> {
+    let mut entries = Vec::new();
 
-    // Validate each server configuration
-    for (name, server) in &config.mcp_servers {
-        if name.trim().is_empty() {
-            return Err("Server name cannot be empty".to_string());
+    for (index, raw_line) in content.lines().enumerate() {
+        let line = raw_line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
         }
 
-        if server.command.trim().is_empty() {
-            return Err(format!("Server '{}' has an empty command", name));
-        }
+        let line = line.strip_prefix("export ").unwrap_or(line).trim_start();
+        let Some((key, value)) = line.split_once('=') else {
+            return Err(format!("Line {}: expected KEY=VALUE", index + 1));
+        };
 
-        // Check for common command issues
-        if server.command.contains(" ") && !server.command.starts_with("\"") {
-            return Err(format!("Server '{}' command contains spaces but is not quoted. Consider moving arguments to the 'args' array", name));
+        let key = key.trim();
+        if !is_valid_env_key(key) {
+            return Err(format!(
+                "Line {}: invalid variable name '{}'",
+                index + 1,
+                key
+            ));
         }
+
+        let value = value.trim();
+        let value = if value.len() >= 2 && value.starts_with('"') && value.ends_with('"') {
+            value[1..value.len() - 1]
+                .replace("\\n", "\n")
+                .replace("\\\"", "\"")
+                .replace("\\\\", "\\")
+        } else if value.len() >= 2 && value.starts_with('\'') && value.ends_with('\'') {
+            value[1..value.len() - 1].to_string()
+        } else {
+            // Unquoted values may carry a trailing ` # comment`
+            match value.find(" #") {
+                Some(comment_start) => value[..comment_start].trim_end().to_string(),
+                None => value.to_string(),
+            }
+        };
+
+        entries.push((key.to_string(), value));
     }
 
-    Ok(())
+    Ok(entries)
 }
 
-fn save_server_config(
-    name: String,
-    server_data: Option<McpServerEdit>,
-    is_new: bool,
-    custom_path: Option<String>,
-) -> Result<SaveResult, String> {
-    let config_path = resolve_config_path(custom_path)?;
+fn is_valid_env_key(key: &str) -> bool {
+    key.chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
 
-    // Create backup
-    let backup_path = format!("{}.backup", config_path);
-    fs::copy(&config_path, &backup_path).map_err(|e| format!("Failed to create backup: {}", e))?;
+fn is_secret_env_key(key: &str) -> bool {
+    let upper = key.to_uppercase();
+    ["KEY", "TOKEN", "SECRET", "PASSWORD"]
+        .iter()
+        .any(|marker| upper.contains(marker))
+}
+
+// Replace secret-looking env values with keychain placeholders
+fn move_secrets_to_keychain(
+    server_name: &str,
+    env: HashMap<String, String>,
+) -> Result<HashMap<String, String>, String> {
+    env.into_iter()
+        .map(|(key, value)| {
+            if is_secret_env_key(&key)
+                && !value.is_empty()
+                && parse_keychain_placeholder(&value).is_none()
+            {
+                let placeholder = store_secret(server_name, &key, &value)?;
+                Ok((key, placeholder))
+            } else {
+                Ok((key, value))
+            }
+        })
+        .collect()
+}
 
-    // Read current config
-    let file_content =
-        fs::read_to_string(&config_path).map_err(|e| format!("Failed to read config: {}", e))?;
+// Resolve keychain placeholders back to their real values for launching a server
+fn resolve_secret_placeholders(
+    env: &HashMap<String, String>,
+) -> Result<HashMap<String, String>, String> {
+    env.iter()
+        .map(|(key, value)| {
+            let resolved = match parse_keychain_placeholder(value) {
+                Some(account) => retrieve_secret(account)?,
+                None => value.clone(),
+            };
+            Ok((key.clone(), resolved))
+        })
+        .collect()
+}
 
-    let mut config: ClaudeConfig =
-        serde_json::from_str(&file_content).map_err(|e| format!("Failed to parse JSON: {}", e))?;
+// OS env vars are referenced from the config as `${env:NAME}`, anywhere inside a value
+const ENV_REFERENCE_PREFIX: &str = "${env:";
 
-    let is_add_or_update = server_data.is_some();
+// Expand `${env:NAME}` references, leaving unknown ones in place and recording their names
+fn expand_env_references(value: &str, unresolved: &mut Vec<String>) -> String {
+    let mut expanded = String::new();
+    let mut rest = value;
 
-    match server_data {
-        Some(data) => {
-            // Add or update server
-            if is_new && config.mcp_servers.contains_key(&name) {
-                return Ok(SaveResult {
-                    success: false,
-                    message: format!("Server '{}' already exists", name),
-                });
+    while let Some(start) = rest.find(ENV_REFERENCE_PREFIX) {
+        let after_prefix = &rest[start + ENV_REFERENCE_PREFIX.len()..];
+        let Some(end) = after_prefix.find('}') else {
+            break;
+        };
+
+        expanded.push_str(&rest[..start]);
+        let name = &after_prefix[..end];
+        match env::var(name) {
+            Ok(os_value) => expanded.push_str(&os_value),
+            Err(_) => {
+                unresolved.push(name.to_string());
+                expanded.push_str(&rest[start..start + ENV_REFERENCE_PREFIX.len() + end + 1]);
             }
+        }
+        rest = &after_prefix[end + 1..];
+    }
 
-            let env = if data.env.is_empty() {
-                None
-            } else {
-                Some(data.env)
-            };
+    expanded.push_str(rest);
+    expanded
+}
 
-            config.mcp_servers.insert(
-                name.clone(),
-                McpServer {
-                    command: data.command,
-                    args: data.args,
-                    env,
-                },
-            );
+// Expand shell-style `$NAME` and `${NAME}` against the OS environment, leaving unknown
+// ones in place and recording their names. `${env:NAME}` references are left alone.
+fn expand_shell_variables(value: &str, unresolved: &mut Vec<String>) -> String {
+    let mut expanded = String::new();
+    let mut rest = value;
+
+    while let Some(start) = rest.find('$') {
+        expanded.push_str(&rest[..start]);
+        let after_dollar = &rest[start + 1..];
+        let (name, reference_len) = match after_dollar.strip_prefix('{') {
+            Some(braced) => match braced.find('}') {
+                Some(end) => (&braced[..end], end + 2),
+                None => ("", 0),
+            },
+            None => {
+                let end = after_dollar
+                    .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                    .unwrap_or(after_dollar.len());
+                (&after_dollar[..end], end)
+            }
+        };
+
+        // A lone `$` or something like `${env:NAME}` is kept as written
+        if !is_valid_env_key(name) {
+            expanded.push('$');
+            rest = after_dollar;
+            continue;
         }
-        None => {
-            // Delete server
-            if config.mcp_servers.remove(&name).is_none() {
-                return Ok(SaveResult {
-                    success: false,
-                    message: format!("Server '{}' not found", name),
-                });
+
+        match env::var(name) {
+            Ok(os_value) => expanded.push_str(&os_value),
+            Err(_) => {
+                unresolved.push(name.to_string());
+                expanded.push_str(&rest[start..start + 1 + reference_len]);
             }
         }
+        rest = &after_dollar[reference_len..];
     }
 
-    // Write updated config
-    let updated_content = serde_json::to_string_pretty(&config)
-        .map_err(|e| format!("Failed to serialize config: {}", e))?;
+    expanded.push_str(rest);
+    expanded
+}
 
-    fs::write(&config_path, updated_content)
-        .map_err(|e| format!("Failed to write config: {}", e))?;
+// How args such as `-v $HOME/data:/data` read once their variables are expanded, to
+// check a launch will resolve them. Only a preview; nothing is stored.
+#[tauri::command]
+fn preview_expanded_args(args: Vec<String>) -> ExpandedArgsPreview {
+    let mut unresolved = Vec::new();
+    let args = args
+        .into_iter()
+        .map(|raw| {
+            let expanded = expand_shell_variables(&raw, &mut unresolved);
+            ExpandedArg { raw, expanded }
+        })
+        .collect();
 
-    let action = if is_add_or_update {
-        if is_new {
-            "added"
-        } else {
-            "updated"
-        }
-    } else {
-        "deleted"
-    };
+    unresolved.sort();
+    unresolved.dedup();
+    ExpandedArgsPreview { args, unresolved }
+}
 
-    Ok(SaveResult {
-        success: true,
-        message: format!("Server '{}' {} successfully", name, action),
-    })
+// Everything needed before launching a server: keychain secrets, then OS env references
+fn resolve_launch_env(env: &HashMap<String, String>) -> Result<ResolvedEnv, String> {
+    let mut unresolved = Vec::new();
+    let env = resolve_secret_placeholders(env)?
+        .into_iter()
+        .map(|(key, value)| {
+            let expanded = expand_env_references(&value, &mut unresolved);
+            (key, expanded)
+        })
+        .collect();
+
+    unresolved.sort();
+    unresolved.dedup();
+    Ok(ResolvedEnv { env, unresolved })
 }
 
-fn get_claude_config_path() -> Result<String, String> {
+// Resolve a command the way a shell would: absolute/relative paths are checked directly,
+// bare names are searched in PATH (trying PATHEXT extensions on Windows)
+fn resolve_command_path(command: &str) -> Option<std::path::PathBuf> {
+    let command = command.trim().trim_matches('"');
+    if command.is_empty() {
+        return None;
+    }
+
+    let command_path = Path::new(command);
+    if command_path.is_absolute() || command_path.components().count() > 1 {
+        return find_executable(command_path);
+    }
+
+    let path_var = env::var_os("PATH")?;
+    env::split_paths(&path_var).find_map(|dir| find_executable(&dir.join(command)))
+}
+
+#[cfg(target_os = "windows")]
+fn find_executable(candidate: &Path) -> Option<std::path::PathBuf> {
+    if candidate.extension().is_some() && candidate.is_file() {
+        return Some(candidate.to_path_buf());
+    }
+
+    let path_ext = env::var("PATHEXT").unwrap_or_else(|_| ".COM;.EXE;.BAT;.CMD".to_string());
+    path_ext
+        .split(';')
+        .filter(|ext| !ext.is_empty())
+        .map(|ext| {
+            let mut with_ext = candidate.as_os_str().to_os_string();
+            with_ext.push(ext);
+            std::path::PathBuf::from(with_ext)
+        })
+        .find(|path| path.is_file())
+}
+
+#[cfg(not(target_os = "windows"))]
+fn find_executable(candidate: &Path) -> Option<std::path::PathBuf> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let metadata = fs::metadata(candidate).ok()?;
+    if metadata.is_file() && metadata.permissions().mode() & 0o111 != 0 {
+        Some(candidate.to_path_buf())
+    } else {
+        None
+    }
+}
+
+// Clients with a known default config location
+const KNOWN_CLIENTS: &[&str] = &["claude", "cursor", "continue"];
+
+fn get_home_dir() -> Result<String, String> {
     #[cfg(target_os = "windows")]
     {
-        let appdata =
-            env::var("APPDATA").map_err(|_| "Could not determine APPDATA directory".to_string())?;
-        Ok(format!("{}\\Claude\\claude_desktop_config.json", appdata))
+        env::var("USERPROFILE").map_err(|_| "Could not determine home directory".to_string())
     }
 
-    #[cfg(target_os = "macos")]
+    #[cfg(not(target_os = "windows"))]
     {
-        let home_dir =
-            env::var("HOME").map_err(|_| "Could not determine home directory".to_string())?;
-        Ok(format!(
-            "{}/Library/Application Support/Claude/claude_desktop_config.json",
-            home_dir
-        ))
+        env::var("HOME").map_err(|_| "Could not determine home directory".to_string())
     }
+}
 
-    #[cfg(target_os = "linux")]
-    {
-        let home_dir =
-            env::var("HOME").map_err(|_| "Could not determine home directory".to_string())?;
-        Ok(format!(
-            "{}/.config/Claude/claude_desktop_config.json",
-            home_dir
-        ))
+fn resolve_config_path_for_client(client: &str) -> Result<String, String> {
+    match client.to_lowercase().as_str() {
+        "claude" => get_claude_config_path(),
+        "cursor" => {
+            let home_dir = get_home_dir()?;
+            #[cfg(target_os = "windows")]
+            {
+                Ok(format!("{}\\.cursor\\mcp.json", home_dir))
+            }
+            #[cfg(not(target_os = "windows"))]
+            {
+                Ok(format!("{}/.cursor/mcp.json", home_dir))
+            }
+        }
+        "continue" => {
+            let home_dir = get_home_dir()?;
+            #[cfg(target_os = "windows")]
+            {
+                Ok(format!("{}\\.continue\\config.json", home_dir))
+            }
+            #[cfg(not(target_os = "windows"))]
+            {
+                Ok(format!("{}/.continue/config.json", home_dir))
+            }
+        }
+        _ => Err(format!("Unknown MCP client '{}'", client)),
     }
+}
 
-    #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
-    {
-        Err("Unsupported operating system".to_string())
+// Resolve an optional target name to a config path, falling back to the custom path.
+// Targets configured in settings take precedence over the built-in client defaults.
+async fn resolve_target_path(
+    state: &AppState,
+    target: Option<String>,
+    custom_path: Option<String>,
+) -> Result<Option<String>, String> {
+    let target = match target {
+        Some(target) if !target.trim().is_empty() => target,
+        _ => return Ok(custom_path),
+    };
+
+    let configured = {
+        let settings_guard = state.settings_cache.read().await;
+        settings_guard
+            .config_targets
+            .iter()
+            .find(|t| t.name == target)
+            .map(|t| t.path.clone())
+    };
+
+    match configured {
+        Some(path) => Ok(Some(path)),
+        None => resolve_config_path_for_client(&target).map(Some),
     }
 }
 
 fn resolve_config_path(custom_path: Option<String>) -> Result<String, String> {
-    if let Some(path) = custom_path {
-        if path.trim().is_empty() {
-            get_claude_config_path()
-        } else {
-            Ok(path)
-        }
-    } else {
-        get_claude_config_path()
+    match custom_path {
+        Some(path) if !path.trim().is_empty() => expand_user_path(path.trim()),
+        _ => get_claude_config_path(),
+    }
+}
+
+// Expand a leading `~` to the home directory. Other relative paths are also taken
+// relative to the home directory, because a GUI app's working directory depends on
+// how it was launched. Absolute paths are returned unchanged.
+fn expand_user_path(path: &str) -> Result<String, String> {
+    if Path::new(path).is_absolute() {
+        return Ok(path.to_string());
     }
+
+    let home_dir = get_home_dir()?;
+    let relative = if path == "~" {
+        return Ok(home_dir);
+    } else if let Some(rest) = path.strip_prefix("~/").or_else(|| path.strip_prefix("~\\")) {
+        rest
+    } else {
+        path
+    };
+
+    Ok(Path::new(&home_dir)
+        .join(relative)
+        .to_string_lossy()
+        .to_string())
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
@@ -1285,43 +7169,107 @@ pub fn run() {
             greet,
             parse_claude_json,
             get_server_details,
+            resolve_server_secrets,
+            resolve_server_env,
             update_server,
             add_server,
             delete_server,
+            delete_servers,
+            set_env_across_servers,
+            get_config_stats,
+            get_config_size_warning,
+            find_duplicate_servers,
+            validate_full_config,
+            lint_config_file,
+            try_autofix_json,
+            get_server_as_json,
+            preview_config_output,
+            group_installed_by_category,
+            get_installed_servers_by_type,
+            detect_port_conflicts,
+            list_required_env_keys,
+            merge_duplicates,
+            sort_config_servers,
+            rename_server,
+            duplicate_server,
+            enable_server,
+            disable_server,
+            import_servers_from_json,
+            export_servers,
+            export_config_as,
+            import_config_from,
             get_default_config_path,
+            list_config_targets,
             load_app_settings,
             save_app_settings,
+            set_dark_mode,
             get_settings_path,
+            get_platform_paths,
+            check_config_writable,
+            save_profile,
+            load_profile,
+            list_profiles,
+            delete_profile,
             get_preset_servers,
             get_preset_servers_by_category,
             get_preset_server_categories,
             get_preset_server_by_name,
+            add_custom_preset,
+            delete_custom_preset,
+            export_preset_database,
             get_preset_servers_by_type,
+            scaffold_server,
+            get_full_presets_filtered,
+            preview_expanded_args,
+            get_preset_servers_by_api_requirement,
+            install_preset_server,
+            add_server_with_env_file,
             get_server_types,
+            search_servers,
+            validate_config_schema,
+            get_server_metadata,
+            compare_with_preset,
+            get_preset_install_status,
             validate_server_config,
+            check_command_availability,
             get_backup_info,
+            get_audit_log_tail,
+            list_backups,
+            list_config_artifacts,
+            cleanup_artifacts,
+            prepare_restore,
+            diff_backups,
             restore_from_backup,
             create_manual_backup,
+            undo_last_change,
+            redo_last_change,
             open_file_location,
             start_mcp_server,
             stop_mcp_server,
+            restart_mcp_server,
+            reload_settings,
             get_mcp_server_status,
-            validate_mcp_port
+            get_mcp_tool_schemas,
+            list_mcp_tools,
+            validate_mcp_port,
+            validate_sse_path,
+            generate_mcp_token
         ])
         .setup(|_app| {
-            println!("🚀 MCP Manager started with integrated MCP server support");
-            println!("📱 Desktop GUI: Available");
-            println!("🔗 MCP Server: Ready for Claude Desktop integration");
-            println!("⚙️ Auto-start: Will check settings and start if enabled");
+            // Install the tracing subscriber once for the whole process; starting the
+            // MCP server later only adjusts its level
+            mcp_server::init_tracing(&default_mcp_log_level());
+            tracing::info!("MCP Manager started with integrated MCP server support");
             // Load settings into cache on startup
             let app_state = _app.state::<AppState>();
             let state_clone = app_state.inner().clone();
+            let app_handle = _app.handle().clone();
             tauri::async_runtime::spawn(async move {
                 // Load settings on startup to populate cache
                 let settings_path = match get_settings_path() {
                     Ok(path) => path,
                     Err(e) => {
-                        eprintln!("⚠️ Failed to get settings path: {}", e);
+                        tracing::warn!("Failed to get settings path: {}", e);
                         return;
                     }
                 };
@@ -1330,17 +7278,15 @@ pub fn run() {
                     AppSettings::default()
                 } else {
                     match fs::read_to_string(&settings_path) {
-                        Ok(content) => {
-                            match serde_json::from_str(&content) {
-                                Ok(settings) => settings,
-                                Err(e) => {
-                                    eprintln!("⚠️ Failed to parse settings: {}", e);
-                                    AppSettings::default()
-                                }
+                        Ok(content) => match serde_json::from_str(&content) {
+                            Ok(settings) => settings,
+                            Err(e) => {
+                                tracing::warn!("Failed to parse settings: {}", e);
+                                AppSettings::default()
                             }
-                        }
+                        },
                         Err(e) => {
-                            eprintln!("⚠️ Failed to read settings file: {}", e);
+                            tracing::warn!("Failed to read settings file: {}", e);
                             AppSettings::default()
                         }
                     }
@@ -1352,30 +7298,742 @@ pub fn run() {
                     *settings_cache = settings.clone();
                 }
 
-                println!("✅ Settings loaded on startup: MCP server enabled = {}", settings.mcp_server_enabled);
+                mcp_server::init_tracing(&settings.mcp_log_level);
+                tracing::info!(
+                    "Settings loaded on startup: MCP server enabled = {}",
+                    settings.mcp_server_enabled
+                );
+
+                // Watch the config file for external changes
+                match resolve_config_path(Some(settings.claude_config_path.clone())) {
+                    Ok(config_path) => {
+                        if let Err(e) = config_watcher::start_config_watcher(
+                            app_handle,
+                            state_clone.clone(),
+                            config_path,
+                        )
+                        .await
+                        {
+                            tracing::warn!("Failed to watch config file: {}", e);
+                        }
+                    }
+                    Err(e) => tracing::warn!("Failed to resolve config path: {}", e),
+                }
 
                 // Auto-start MCP server if enabled in settings
                 if settings.mcp_server_enabled {
-                    println!("🚀 Auto-starting MCP server...");
+                    tracing::info!("Auto-starting MCP server");
                     match internal_start_mcp_server(&state_clone).await {
                         Ok(result) => {
                             if result.success {
-                                println!("✅ MCP server auto-started successfully: {}", result.message);
+                                tracing::info!("MCP server auto-started: {}", result.message);
                             } else {
-                                println!("⚠️ MCP server auto-start failed: {}", result.message);
+                                tracing::warn!("MCP server auto-start failed: {}", result.message);
                             }
                         }
                         Err(e) => {
-                            println!("❌ MCP server auto-start error: {}", e);
+                            tracing::error!("MCP server auto-start error: {}", e);
                         }
                     }
                 } else {
-                    println!("ℹ️ MCP server auto-start skipped (disabled in settings)");
+                    tracing::info!("MCP server auto-start skipped (disabled in settings)");
                 }
             });
 
             Ok(())
         })
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app_handle, event| {
+            if let tauri::RunEvent::Exit = event {
+                // Wait out an in-flight config write, then keep the lock so no new one
+                // can start before the process is gone
+                let state = app_handle.state::<AppState>();
+                let write_guard = tauri::async_runtime::block_on(state.lock_config_writes());
+                std::mem::forget(write_guard);
+            }
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A fresh directory under the system temp dir, unique to this test process
+    fn temp_dir(name: &str) -> std::path::PathBuf {
+        let dir = env::temp_dir().join(format!("mcp-manager-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn server_edit(command: &str, args: &[&str]) -> McpServerEdit {
+        McpServerEdit {
+            command: command.to_string(),
+            args: args.iter().map(|arg| arg.to_string()).collect(),
+            env: HashMap::new(),
+            cwd: None,
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn concurrent_adds_both_survive() {
+        let dir = temp_dir("concurrent-adds");
+        let config_path = dir
+            .join("claude_desktop_config.json")
+            .to_string_lossy()
+            .to_string();
+        fs::write(&config_path, r#"{"mcpServers": {}}"#).unwrap();
+
+        let state = AppState::new();
+        let add = |name: &'static str| {
+            let state = state.clone();
+            let config_path = config_path.clone();
+            tokio::spawn(async move {
+                internal_add_server(
+                    &state,
+                    name.to_string(),
+                    server_edit("node", &[name]),
+                    Some(config_path),
+                    false,
+                    None,
+                )
+                .await
+            })
+        };
+        let (first, second) = tokio::join!(add("first"), add("second"));
+        assert!(first.unwrap().unwrap().success);
+        assert!(second.unwrap().unwrap().success);
+
+        let (config, _) = state.load_config(Some(config_path)).await.unwrap();
+        assert!(config.mcp_servers.contains_key("first"));
+        assert!(config.mcp_servers.contains_key("second"));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn fuzzy_score_ranks_match_kinds() {
+        assert_eq!(fuzzy_score("brave-search", "brave-search"), 1.0);
+        assert_eq!(fuzzy_score("brave", "brave-search"), 0.9);
+        assert_eq!(fuzzy_score("search", "Brave-Search"), 0.8);
+        assert!(fuzzy_score("brv", "brave-search") > 0.5);
+        assert!(fuzzy_score("brabe", "brave-search") > 0.0);
+        assert!(fuzzy_score("brv", "brave-search") < fuzzy_score("search", "brave-search"));
+    }
+
+    #[test]
+    fn fuzzy_score_rejects_unrelated_and_empty_queries() {
+        assert_eq!(fuzzy_score("xyz", "brave-search"), 0.0);
+        assert_eq!(fuzzy_score("", "brave-search"), 0.0);
+        assert_eq!(fuzzy_score("   ", "brave-search"), 0.0);
+    }
+
+    #[tokio::test]
+    async fn unknown_top_level_keys_survive_add_and_delete() {
+        let dir = temp_dir("preserve-keys");
+        let config_path = dir
+            .join("claude_desktop_config.json")
+            .to_string_lossy()
+            .to_string();
+        fs::write(
+            &config_path,
+            r#"{"globalShortcut": "Ctrl+Space", "mcpServers": {}, "theme": "dark"}"#,
+        )
+        .unwrap();
+
+        let state = AppState::new();
+        let added = internal_add_server(
+            &state,
+            "fetch".to_string(),
+            server_edit("uvx", &["mcp-server-fetch"]),
+            Some(config_path.clone()),
+            false,
+            None,
+        )
+        .await
+        .unwrap();
+        assert!(added.success);
+        let deleted =
+            internal_delete_server(&state, "fetch".to_string(), Some(config_path.clone()), None)
+                .await
+                .unwrap();
+        assert!(deleted.success);
+
+        let saved: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&config_path).unwrap()).unwrap();
+        let keys: Vec<&str> = saved
+            .as_object()
+            .unwrap()
+            .keys()
+            .map(String::as_str)
+            .collect();
+        assert_eq!(keys, ["globalShortcut", "mcpServers", "theme"]);
+        assert_eq!(saved["globalShortcut"], "Ctrl+Space");
+        assert_eq!(saved["theme"], "dark");
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn serialize_config_preserving_keys_drops_empty_disabled_servers() {
+        let dir = temp_dir("serialize-disabled");
+        let config_path = dir.join("config.json").to_string_lossy().to_string();
+        fs::write(
+            &config_path,
+            r#"{"_disabledServers": {"old": {"command": "npx"}}, "mcpServers": {}, "theme": "dark"}"#,
+        )
+        .unwrap();
+
+        let output =
+            serialize_config_preserving_keys(&config_path, &ClaudeConfig::default()).unwrap();
+        let saved: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert!(saved.get("_disabledServers").is_none());
+        assert_eq!(saved["mcpServers"], serde_json::json!({}));
+        assert_eq!(saved["theme"], "dark");
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn config_round_trips_through_every_format() {
+        let mut config = ClaudeConfig::default();
+        config.mcp_servers.insert(
+            "github".to_string(),
+            McpServer {
+                command: "npx".to_string(),
+                args: vec![
+                    "-y".to_string(),
+                    "@modelcontextprotocol/server-github".to_string(),
+                ],
+                env: Some(HashMap::from([
+                    ("GITHUB_TOKEN".to_string(), "ghp_123".to_string()),
+                    ("DEBUG".to_string(), "1".to_string()),
+                ])),
+                cwd: Some("/tmp".to_string()),
+            },
+        );
+        config.disabled_servers.insert(
+            "fetch".to_string(),
+            McpServer {
+                command: "uvx".to_string(),
+                args: vec!["mcp-server-fetch".to_string()],
+                env: None,
+                cwd: None,
+            },
+        );
+
+        for format in [ConfigFormat::Json, ConfigFormat::Yaml, ConfigFormat::Toml] {
+            let text = format.serialize(&config).unwrap();
+            let parsed = format.deserialize(&text).unwrap();
+            assert_eq!(parsed.mcp_servers, config.mcp_servers, "{:?}", format);
+            assert_eq!(
+                parsed.disabled_servers, config.disabled_servers,
+                "{:?}",
+                format
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn repeated_command_lookups_hit_the_cache() {
+        let state = AppState::new();
+        let command = "mcp-manager-test-missing-command";
+        assert_eq!(state.resolve_command_cached(command).await, None);
+        assert!(state.command_cache.read().await.contains_key(command));
+
+        // A fresh entry is returned as is, without scanning PATH again
+        let cached = std::path::PathBuf::from("/cached/mcp-manager-test-missing-command");
+        state.command_cache.write().await.insert(
+            command.to_string(),
+            (Some(cached.clone()), std::time::Instant::now()),
+        );
+        assert_eq!(state.resolve_command_cached(command).await, Some(cached));
+    }
+
+    #[tokio::test]
+    async fn expired_command_lookups_are_resolved_again() {
+        let state = AppState::new();
+        state.settings_cache.write().await.command_cache_ttl_secs = 0;
+        let command = "mcp-manager-test-missing-command";
+        state.command_cache.write().await.insert(
+            command.to_string(),
+            (
+                Some(std::path::PathBuf::from(
+                    "/cached/mcp-manager-test-missing-command",
+                )),
+                std::time::Instant::now(),
+            ),
+        );
+        assert_eq!(state.resolve_command_cached(command).await, None);
+    }
+
+    #[test]
+    fn error_context_lines_marks_the_column() {
+        let content = "{\n  \"a\": 1,\n  \"b\"\n}";
+        assert_eq!(
+            error_context_lines(content, 3, 6),
+            vec!["2 |   \"a\": 1,", "3 |   \"b\"", "  |      ^", "4 | }"]
+        );
+    }
+
+    #[test]
+    fn error_context_lines_clamps_positions_past_the_end() {
+        assert_eq!(error_context_lines("{", 9, 40), vec!["1 | {", "  |  ^"]);
+        assert!(error_context_lines("", 1, 1).is_empty());
+    }
+
+    #[test]
+    fn parse_env_file_handles_comments_exports_and_quotes() {
+        let content = "# comment\n\nexport A=1\nB=\"two words\"\nC='x # y'\nD=plain # note\n";
+        assert_eq!(
+            parse_env_file(content).unwrap(),
+            vec![
+                ("A".to_string(), "1".to_string()),
+                ("B".to_string(), "two words".to_string()),
+                ("C".to_string(), "x # y".to_string()),
+                ("D".to_string(), "plain".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_env_file_rejects_malformed_lines() {
+        assert_eq!(
+            parse_env_file("NO_EQUALS").unwrap_err(),
+            "Line 1: expected KEY=VALUE"
+        );
+        assert_eq!(
+            parse_env_file("A=1\n1X=2").unwrap_err(),
+            "Line 2: invalid variable name '1X'"
+        );
+    }
+
+    #[test]
+    fn normalize_config_content_strips_bom_and_fills_blank_files() {
+        assert_eq!(
+            normalize_config_content("\u{feff}{\"mcpServers\": {}}"),
+            "{\"mcpServers\": {}}"
+        );
+        assert_eq!(normalize_config_content(""), r#"{"mcpServers": {}}"#);
+        assert_eq!(normalize_config_content("  \n\t"), r#"{"mcpServers": {}}"#);
+        assert_eq!(normalize_config_content("{}"), "{}");
+    }
+
+    #[tokio::test]
+    async fn load_config_accepts_bom_and_empty_files() {
+        let dir = temp_dir("bom-and-empty");
+        let bom_path = dir.join("bom.json").to_string_lossy().to_string();
+        fs::write(
+            &bom_path,
+            "\u{feff}{\"mcpServers\": {\"fetch\": {\"command\": \"uvx\"}}}",
+        )
+        .unwrap();
+        let empty_path = dir.join("empty.json").to_string_lossy().to_string();
+        fs::write(&empty_path, "").unwrap();
+
+        let state = AppState::new();
+        let (config, _) = state.load_config(Some(bom_path.clone())).await.unwrap();
+        assert!(config.mcp_servers.contains_key("fetch"));
+        assert!(get_server_details("fetch".to_string(), Some(bom_path)).is_ok());
+        let (config, _) = state.load_config(Some(empty_path)).await.unwrap();
+        assert!(config.mcp_servers.is_empty());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    // Starting the MCP server again re-runs init_tracing; that must neither try to install
+    // a second global subscriber nor ignore the new level
+    #[test]
+    fn init_tracing_can_run_on_every_server_start() {
+        // RUST_LOG takes precedence over the level passed in
+        if env::var_os("RUST_LOG").is_some() {
+            return;
+        }
+        let debug_enabled = || tracing::enabled!(target: "mcp_manager_lib", tracing::Level::DEBUG);
+
+        mcp_server::init_tracing("info");
+        assert!(!debug_enabled());
+        mcp_server::init_tracing("mcp_manager_lib=debug");
+        assert!(debug_enabled());
+        mcp_server::init_tracing("info");
+        assert!(!debug_enabled());
+        assert!(tracing::enabled!(target: "mcp_manager_lib", tracing::Level::INFO));
+    }
+
+    #[test]
+    fn expand_user_path_resolves_against_home() {
+        let home = get_home_dir().unwrap();
+        let under_home = |relative: &str| {
+            Path::new(&home)
+                .join(relative)
+                .to_string_lossy()
+                .to_string()
+        };
+
+        assert_eq!(expand_user_path("~").unwrap(), home);
+        assert_eq!(
+            expand_user_path("~/my/config.json").unwrap(),
+            under_home("my/config.json")
+        );
+        assert_eq!(
+            expand_user_path("my/config.json").unwrap(),
+            under_home("my/config.json")
+        );
+    }
+
+    #[test]
+    fn expand_user_path_keeps_absolute_paths() {
+        let absolute = env::temp_dir()
+            .join("config.json")
+            .to_string_lossy()
+            .to_string();
+        assert_eq!(expand_user_path(&absolute).unwrap(), absolute);
+    }
+
+    #[test]
+    fn self_preset_points_at_configured_port_and_sse_path() {
+        let preset = builtin_preset_servers()
+            .into_iter()
+            .find(|preset| preset.name == SELF_PRESET_NAME)
+            .unwrap();
+        let settings = AppSettings {
+            mcp_server_port: 9123,
+            mcp_sse_path: "/events".to_string(),
+            ..AppSettings::default()
+        };
+
+        let mut args = preset.args.clone();
+        point_self_preset_at_settings(&mut args, &settings);
+        assert_eq!(
+            args,
+            [
+                "-y",
+                "supergateway",
+                "--sse",
+                "http://localhost:9123/events"
+            ]
+        );
+    }
+
+    #[test]
+    fn normalize_preset_converts_legacy_api_key_fields() {
+        let legacy: PresetServer = serde_json::from_str(
+            r#"{
+                "name": "legacy",
+                "description": "Old-style custom preset",
+                "category": "Custom",
+                "serverType": "npx",
+                "command": "npx",
+                "args": ["-y", "legacy-server"],
+                "requiresApiKey": true,
+                "apiKeyName": "LEGACY_TOKEN",
+                "apiKeyDescription": "Token for the legacy API"
+            }"#,
+        )
+        .unwrap();
+
+        let preset = normalize_preset(legacy);
+        assert_eq!(preset.api_keys.len(), 1);
+        assert_eq!(preset.api_keys[0].name, "LEGACY_TOKEN");
+        assert_eq!(preset.api_keys[0].description, "Token for the legacy API");
+        assert!(preset.api_keys[0].required);
+    }
+
+    #[test]
+    fn normalize_preset_keeps_existing_api_keys() {
+        let preset: PresetServer = serde_json::from_str(
+            r#"{
+                "name": "modern",
+                "description": "New-style custom preset",
+                "category": "Custom",
+                "serverType": "npx",
+                "command": "npx",
+                "args": [],
+                "apiKeys": [{"name": "NEW_TOKEN", "description": "Token", "required": false}],
+                "apiKeyName": "OLD_TOKEN"
+            }"#,
+        )
+        .unwrap();
+
+        let preset = normalize_preset(preset);
+        assert_eq!(preset.api_keys.len(), 1);
+        assert_eq!(preset.api_keys[0].name, "NEW_TOKEN");
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn stop_waits_for_an_in_flight_write() {
+        use std::sync::atomic::{AtomicBool, Ordering};
+
+        let state = AppState::new();
+        let write_finished = Arc::new(AtomicBool::new(false));
+        let (locked_tx, locked_rx) = tokio::sync::oneshot::channel();
+
+        // Stand-in for a slow save_config holding the write lock
+        let writer = {
+            let state = state.clone();
+            let write_finished = write_finished.clone();
+            tokio::spawn(async move {
+                let _write_guard = state.lock_config_writes().await;
+                locked_tx.send(()).unwrap();
+                tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+                write_finished.store(true, Ordering::SeqCst);
+            })
+        };
+        locked_rx.await.unwrap();
+
+        let result = internal_stop_mcp_server(&state).await.unwrap();
+        assert!(result.success);
+        assert!(write_finished.load(Ordering::SeqCst));
+        writer.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn overwrite_replaces_a_disabled_server_in_one_save() {
+        let dir = temp_dir("overwrite-disabled");
+        let config_path = dir
+            .join("claude_desktop_config.json")
+            .to_string_lossy()
+            .to_string();
+        fs::write(
+            &config_path,
+            r#"{"mcpServers": {}, "_disabledServers": {"fetch": {"command": "old", "args": []}}}"#,
+        )
+        .unwrap();
+
+        let state = AppState::new();
+        let result = internal_add_server_with_conflict(
+            &state,
+            "fetch".to_string(),
+            server_edit("uvx", &["mcp-server-fetch"]),
+            Some(config_path.clone()),
+            false,
+            mcp_server::OnConflict::Overwrite,
+            None,
+        )
+        .await
+        .unwrap();
+        assert!(result.success);
+
+        let (config, _) = state.load_config(Some(config_path)).await.unwrap();
+        assert_eq!(config.mcp_servers["fetch"].command, "uvx");
+        assert!(config.disabled_servers.is_empty());
+        assert_eq!(state.undo_stack.read().await.len(), 1);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn dry_run_matches_the_real_commands() {
+        let dir = temp_dir("dry-run");
+        let config_path = dir
+            .join("claude_desktop_config.json")
+            .to_string_lossy()
+            .to_string();
+        fs::write(
+            &config_path,
+            r#"{"mcpServers": {}, "_disabledServers": {"fetch": {"command": "uvx", "args": []}}}"#,
+        )
+        .unwrap();
+        let state = AppState::new();
+
+        let missing = internal_dry_run_server_change(
+            &state,
+            "missing".to_string(),
+            Some(server_edit("node", &[])),
+            false,
+            Some(config_path.clone()),
+        )
+        .await
+        .unwrap();
+        assert!(!missing.success);
+        assert_eq!(missing.message, "Server 'missing' not found");
+
+        let duplicate = internal_dry_run_server_change(
+            &state,
+            "fetch".to_string(),
+            Some(server_edit("node", &[])),
+            true,
+            Some(config_path.clone()),
+        )
+        .await
+        .unwrap();
+        assert!(!duplicate.success);
+
+        let mut bad_env = server_edit("node", &[]);
+        bad_env.env.insert("1BAD".to_string(), "value".to_string());
+        let invalid = internal_dry_run_server_change(
+            &state,
+            "other".to_string(),
+            Some(bad_env),
+            true,
+            Some(config_path.clone()),
+        )
+        .await
+        .unwrap();
+        assert!(!invalid.success);
+
+        let deleted = internal_dry_run_server_change(
+            &state,
+            "fetch".to_string(),
+            None,
+            false,
+            Some(config_path),
+        )
+        .await
+        .unwrap();
+        assert!(deleted.success);
+        assert_eq!(deleted.message, "Server 'fetch' would be deleted (dry run)");
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn restore_only_accepts_own_backups_and_can_be_undone() {
+        let dir = temp_dir("restore");
+        let config_path = dir
+            .join("claude_desktop_config.json")
+            .to_string_lossy()
+            .to_string();
+        fs::write(&config_path, r#"{"mcpServers": {}}"#).unwrap();
+        let backup_path = format!("{}.backup", config_path);
+        fs::write(
+            &backup_path,
+            "\u{feff}{\"globalShortcut\": \"Ctrl+M\", \"mcpServers\": {\"fetch\": {\"command\": \"uvx\", \"args\": []}}}",
+        )
+        .unwrap();
+        let foreign_path = dir.join("other.json").to_string_lossy().to_string();
+        fs::write(&foreign_path, r#"{"mcpServers": {}}"#).unwrap();
+
+        let state = AppState::new();
+        assert!(
+            internal_prepare_restore(&state, Some(config_path.clone()), Some(foreign_path))
+                .await
+                .is_err()
+        );
+
+        let preview = internal_prepare_restore(&state, Some(config_path.clone()), None)
+            .await
+            .unwrap();
+        assert_eq!(preview.added, ["fetch"]);
+
+        let result = internal_restore_from_backup(&state, preview.token, None)
+            .await
+            .unwrap();
+        assert!(result.success);
+        assert!(state.config_cache.read().await[&config_path]
+            .mcp_servers
+            .contains_key("fetch"));
+        assert_eq!(state.undo_stack.read().await.len(), 1);
+        let restored: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&config_path).unwrap()).unwrap();
+        assert_eq!(restored["globalShortcut"], "Ctrl+M");
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn validate_arg_style_flags_mixed_long_options_and_spaces() {
+        let args =
+            |args: &[&str]| -> Vec<String> { args.iter().map(|arg| arg.to_string()).collect() };
+        let mixed =
+            "Args mix '--flag=value' and '--flag value' styles; some servers only accept one";
+
+        assert_eq!(
+            validate_arg_style(&args(&["--local-timezone=UTC", "--port", "3000"])),
+            [mixed]
+        );
+        assert!(validate_arg_style(&args(&["--local-timezone=UTC", "--port=3000"])).is_empty());
+        assert!(
+            validate_arg_style(&args(&["--local-timezone", "UTC", "--port", "3000"])).is_empty()
+        );
+        assert!(validate_arg_style(&args(&["-jar", "server.jar", "-version"])).is_empty());
+
+        assert_eq!(
+            validate_arg_style(&args(&["-y", "server --stdio"])),
+            ["Argument 'server --stdio' contains spaces; if it holds several arguments, split them into separate 'args' entries"]
+        );
+    }
+
+    #[test]
+    fn prune_backups_keeps_manual_backups() {
+        let dir = temp_dir("prune-backups");
+        let config_path = dir
+            .join("claude_desktop_config.json")
+            .to_string_lossy()
+            .to_string();
+        fs::write(&config_path, "{}").unwrap();
+        for timestamp in 1..=3 {
+            fs::write(format!("{}.backup_{}", config_path, timestamp), "{}").unwrap();
+        }
+        fs::write(format!("{}.manual_backup_1", config_path), "{}").unwrap();
+
+        prune_backups(&config_path, 1);
+
+        let remaining: Vec<String> = collect_timestamped_backups(&config_path)
+            .into_iter()
+            .map(|(path, _)| path.file_name().unwrap().to_string_lossy().to_string())
+            .collect();
+        assert_eq!(remaining.len(), 2);
+        assert!(remaining.contains(&"claude_desktop_config.json.manual_backup_1".to_string()));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn check_backup_retention_rejects_zero() {
+        assert!(check_backup_retention(0).is_err());
+        assert!(check_backup_retention(1).is_ok());
+    }
+
+    #[test]
+    fn diff_server_fields_redacts_env_values() {
+        let from = McpServer {
+            command: "npx".to_string(),
+            args: vec!["-y".to_string(), "server".to_string()],
+            env: Some(HashMap::from([("TOKEN".to_string(), "old".to_string())])),
+            cwd: None,
+        };
+        let to = McpServer {
+            args: vec!["server".to_string()],
+            env: Some(HashMap::from([("TOKEN".to_string(), "new".to_string())])),
+            ..from.clone()
+        };
+
+        let fields: Vec<(String, serde_json::Value, serde_json::Value)> =
+            diff_server_fields(&from, &to)
+                .into_iter()
+                .map(|change| (change.field, change.from, change.to))
+                .collect();
+        assert_eq!(
+            fields,
+            [
+                (
+                    "args".to_string(),
+                    serde_json::json!(["-y", "server"]),
+                    serde_json::json!(["server"])
+                ),
+                (
+                    "env.TOKEN".to_string(),
+                    serde_json::json!("<redacted>"),
+                    serde_json::json!("<redacted>")
+                ),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn bulk_delete_reports_read_only_per_name() {
+        let state = AppState::new();
+        state.settings_cache.write().await.read_only = true;
+
+        let results = internal_delete_servers(&state, vec!["a".to_string(), "b".to_string()], None)
+            .await
+            .unwrap();
+        let statuses: Vec<(&str, &str)> = results
+            .iter()
+            .map(|result| (result.name.as_str(), result.status.as_str()))
+            .collect();
+        assert_eq!(statuses, [("a", "read_only"), ("b", "read_only")]);
+    }
 }
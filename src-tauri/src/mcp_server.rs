@@ -1,17 +1,51 @@
-use crate::{AppState, McpServerEdit};
+use crate::{AppState, ArgEdit, McpServerEdit, McpTransport};
 use rmcp::{
-    ServerHandler,
     handler::server::{router::tool::ToolRouter, tool::Parameters, wrapper::Json},
-    model::{ServerCapabilities, ServerInfo},
-    transport::sse_server::{SseServer, SseServerConfig},
+    model::{ServerCapabilities, ServerInfo, Tool},
     schemars, tool, tool_handler, tool_router,
+    transport::sse_server::{SseServer, SseServerConfig},
+    ServerHandler, ServiceExt,
 };
 use serde_json::{json, Value};
 use std::collections::HashMap;
 use std::future::Future;
-use std::net::SocketAddr;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use std::sync::OnceLock;
 use tokio_util::sync::CancellationToken;
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+use tracing_subscriber::{
+    layer::SubscriberExt, reload, util::SubscriberInitExt, EnvFilter, Registry,
+};
+
+// A global subscriber can only be installed once per process, so later starts just
+// swap the filter through this handle
+static LOG_FILTER: OnceLock<reload::Handle<EnvFilter, Registry>> = OnceLock::new();
+
+// Install the tracing subscriber on first use and apply `level` (an EnvFilter
+// directive such as "info" or "mcp_manager=debug"). RUST_LOG takes precedence.
+// Safe to call any number of times, e.g. on every MCP server start.
+pub fn init_tracing(level: &str) {
+    let handle = LOG_FILTER.get_or_init(|| {
+        let (filter, handle) = reload::Layer::new(EnvFilter::new("info"));
+        if tracing_subscriber::registry()
+            .with(filter)
+            // stderr, so logs never mix with the stdio transport's protocol messages
+            .with(tracing_subscriber::fmt::layer().with_writer(std::io::stderr))
+            .try_init()
+            .is_err()
+        {
+            eprintln!("A tracing subscriber is already installed");
+        }
+        handle
+    });
+
+    let filter = EnvFilter::try_from_default_env()
+        .or_else(|_| EnvFilter::try_new(level))
+        .unwrap_or_else(|_| EnvFilter::new("info"));
+    if let Err(e) = handle.reload(filter) {
+        tracing::warn!(error = %e, "Failed to apply log level");
+    }
+}
 
 // Sanitized server info for MCP protocol (shows env keys but not values)
 #[derive(Debug, serde::Serialize)]
@@ -20,16 +54,63 @@ pub struct McpServerInfoSanitized {
     pub command: String,
     pub args: Vec<String>,
     pub env_keys: Vec<String>, // Environment variable keys without values
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<String>,
+    pub enabled: bool,
 }
 
 impl McpServerInfoSanitized {
-    // Convert from McpServerInfo, showing env keys but hiding values
-    fn from_server_info(server_info: &crate::McpServerInfo) -> Self {
+    // Convert from McpServerInfo, hiding env values (and the keys too unless
+    // `expose_env_keys` is set)
+    pub(crate) fn from_server_info(
+        server_info: &crate::McpServerInfo,
+        expose_env_keys: bool,
+    ) -> Self {
         Self {
             name: server_info.name.clone(),
             command: server_info.command.clone(),
             args: server_info.args.clone(),
-            env_keys: server_info.env.keys().cloned().collect(),
+            env_keys: if expose_env_keys {
+                server_info.env.keys().cloned().collect()
+            } else {
+                Vec::new()
+            },
+            cwd: server_info.cwd.clone(),
+            enabled: server_info.enabled,
+        }
+    }
+}
+
+// App settings for MCP protocol; secrets such as the auth token are left out
+#[derive(Debug, serde::Serialize)]
+pub struct AppSettingsSanitized {
+    // The config file the MCP tools read and edit
+    pub config_path: String,
+    pub dark_mode: bool,
+    pub mcp_server_enabled: bool,
+    pub mcp_server_port: u16,
+    pub mcp_sse_path: String,
+    pub mcp_post_path: String,
+    pub mcp_bind_address: String,
+    // Whether clients must authenticate, without the token itself
+    pub auth_required: bool,
+}
+
+impl AppSettingsSanitized {
+    // Convert from AppSettings. The tools all resolve the default Claude config rather
+    // than the GUI's custom path, so that is the path reported.
+    pub(crate) fn from_settings(settings: &crate::AppSettings) -> Self {
+        let config_path = crate::resolve_config_path(None).unwrap_or_default();
+
+        Self {
+            config_path,
+            dark_mode: settings.dark_mode,
+            mcp_server_enabled: settings.mcp_server_enabled,
+            mcp_server_port: settings.mcp_server_port,
+            mcp_sse_path: settings.mcp_sse_path.clone(),
+            mcp_post_path: settings.mcp_post_path.clone(),
+            mcp_bind_address: settings.mcp_bind_address.clone(),
+            auth_required: settings.auth_token().is_some(),
         }
     }
 }
@@ -49,11 +130,14 @@ pub struct PresetServerSanitized {
     pub api_keys: Vec<crate::ApiKeyRequirement>,
     #[serde(rename = "requiresApiKey")]
     pub requires_api_key: bool,
+    #[serde(rename = "argPlaceholders", skip_serializing_if = "Vec::is_empty")]
+    pub arg_placeholders: Vec<crate::ArgPlaceholder>,
 }
 
 impl PresetServerSanitized {
-    // Convert from PresetServer, showing env keys but hiding values
-    fn from_preset_server(preset: &crate::PresetServer) -> Self {
+    // Convert from PresetServer, hiding env values (and the keys too unless
+    // `expose_env_keys` is set)
+    fn from_preset_server(preset: &crate::PresetServer, expose_env_keys: bool) -> Self {
         Self {
             name: preset.name.clone(),
             description: preset.description.clone(),
@@ -61,11 +145,15 @@ impl PresetServerSanitized {
             server_type: preset.server_type.to_string(),
             command: preset.command.clone(),
             args: preset.args.clone(),
-            env_keys: preset.env.as_ref()
+            env_keys: preset
+                .env
+                .as_ref()
+                .filter(|_| expose_env_keys)
                 .map(|env| env.keys().cloned().collect())
                 .unwrap_or_else(Vec::new),
             api_keys: preset.api_keys.clone(),
             requires_api_key: preset.requires_api_key,
+            arg_placeholders: preset.arg_placeholders.clone(),
         }
     }
 }
@@ -81,6 +169,21 @@ pub struct AddMcpServerRequest {
     pub args: Vec<String>,
     #[schemars(description = "Environment variables for the server")]
     pub env: Option<HashMap<String, String>>,
+    #[schemars(description = "Working directory to launch the server in")]
+    pub cwd: Option<String>,
+    #[schemars(description = "Preview the change without writing it (default: false)")]
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Default, serde::Deserialize, schemars::JsonSchema)]
+pub struct ListMcpServersRequest {
+    #[schemars(description = "Number of servers to skip (default: 0)")]
+    #[serde(default)]
+    pub offset: usize,
+    #[schemars(description = "Maximum number of servers to return (default: all)")]
+    #[serde(default)]
+    pub limit: Option<usize>,
 }
 
 #[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
@@ -100,12 +203,64 @@ pub struct UpdateMcpServerRequest {
     pub args: Vec<String>,
     #[schemars(description = "Environment variables for the server")]
     pub env: Option<HashMap<String, String>>,
+    #[schemars(description = "Working directory to launch the server in")]
+    pub cwd: Option<String>,
+    #[schemars(description = "Preview the change without writing it (default: false)")]
+    #[serde(default)]
+    pub dry_run: bool,
 }
 
 #[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
 pub struct DeleteMcpServerRequest {
     #[schemars(description = "Name of the MCP server to delete")]
     pub name: String,
+    #[schemars(description = "Preview the change without writing it (default: false)")]
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct AppendServerArgRequest {
+    #[schemars(description = "Name of the MCP server")]
+    pub name: String,
+    #[schemars(description = "Argument to add at the end of the server's args")]
+    pub value: String,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct RemoveServerArgRequest {
+    #[schemars(description = "Name of the MCP server")]
+    pub name: String,
+    #[schemars(description = "Zero-based index of the argument to remove")]
+    pub index: Option<usize>,
+    #[schemars(
+        description = "Remove the first argument equal to this value instead of using an index"
+    )]
+    pub value: Option<String>,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct SetServerArgRequest {
+    #[schemars(description = "Name of the MCP server")]
+    pub name: String,
+    #[schemars(description = "Zero-based index of the argument to replace")]
+    pub index: usize,
+    #[schemars(description = "New value for the argument")]
+    pub value: String,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct StopServerRequest {
+    #[schemars(description = "Seconds to wait before stopping, 1 to 60 (default 2)")]
+    pub delay_seconds: Option<u64>,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct RenameMcpServerRequest {
+    #[schemars(description = "Current name of the MCP server")]
+    pub old_name: String,
+    #[schemars(description = "New name for the MCP server")]
+    pub new_name: String,
 }
 
 #[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
@@ -120,6 +275,158 @@ pub struct InstallPresetServerRequest {
     pub preset_name: String,
     #[schemars(description = "API keys required for the preset server")]
     pub api_keys: Option<HashMap<String, String>>,
+    #[schemars(description = "Values for the preset's argPlaceholders, keyed by token")]
+    #[serde(default)]
+    pub placeholders: HashMap<String, String>,
+    #[schemars(
+        description = "Pull the docker image before installing a docker-based preset (default false)"
+    )]
+    #[serde(default)]
+    pub pull_image: bool,
+    #[schemars(
+        description = "What to do when a server with the preset's name exists: fail (default), overwrite or keep"
+    )]
+    #[serde(default)]
+    pub on_conflict: OnConflict,
+}
+
+// How a preset install treats an existing server with the same name
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    serde::Serialize,
+    serde::Deserialize,
+    schemars::JsonSchema,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum OnConflict {
+    #[default]
+    Fail,
+    // Back up and replace the existing server
+    Overwrite,
+    // Leave the existing server untouched
+    Keep,
+}
+
+const DEFAULT_STOP_DELAY_SECS: u64 = 2;
+const MAX_STOP_DELAY_SECS: u64 = 60;
+
+// Env map with keys kept and values hidden
+fn redacted_env(env: &HashMap<String, String>) -> Value {
+    env.keys()
+        .map(|key| (key.clone(), json!("<redacted>")))
+        .collect::<serde_json::Map<String, Value>>()
+        .into()
+}
+
+// Server entry as shown in dry-run diffs (env keys kept, values hidden)
+fn redacted_server_entry(server: &crate::McpServer) -> Value {
+    json!({
+        "command": server.command,
+        "args": server.args,
+        "env": server.env.as_ref().map(redacted_env).unwrap_or_else(|| json!({})),
+        "cwd": server.cwd
+    })
+}
+
+fn dry_run_response(
+    result: Result<crate::DryRunResult, String>,
+    name: &str,
+    error_context: &str,
+) -> Json<Value> {
+    match result {
+        Ok(result) if result.success => Json(json!({
+            "success": true,
+            "dry_run": true,
+            "message": result.message,
+            "server_name": name,
+            "diff": {
+                "before": result.before.as_ref().map(redacted_server_entry),
+                "after": result.after.as_ref().map(redacted_server_entry)
+            }
+        })),
+        Ok(result) => Json(json!({
+            "success": false,
+            "dry_run": true,
+            "error": result.message
+        })),
+        Err(e) => Json(json!({
+            "success": false,
+            "dry_run": true,
+            "error": format!("{}: {}", error_context, e)
+        })),
+    }
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct SearchPresetServersRequest {
+    #[schemars(
+        description = "Search text, matched fuzzily against name, description and category"
+    )]
+    pub query: String,
+    #[schemars(description = "Also match installed server names (default: false)")]
+    #[serde(default)]
+    pub include_installed: bool,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct GetPresetsByCategoryRequest {
+    #[schemars(description = "Preset category, as returned by list_preset_categories")]
+    pub category: String,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct GetServersByTypeRequest {
+    #[schemars(
+        description = "docker, npx, uvx or uv, or any other command name to match servers run by that command"
+    )]
+    pub server_type: String,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct GetPresetsByApiRequirementRequest {
+    #[schemars(
+        description = "True for presets that need API keys, false for presets that install without any"
+    )]
+    pub requires_api_key: bool,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct CompareWithPresetRequest {
+    #[schemars(description = "Name of the installed MCP server to compare with its preset")]
+    pub name: String,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct PrepareRestoreRequest {
+    #[schemars(
+        description = "Backup file to restore, as returned by list_backups (default: the latest automatic backup)"
+    )]
+    #[serde(default)]
+    pub backup_path: Option<String>,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct RestoreBackupRequest {
+    #[schemars(
+        description = "Confirmation token returned by prepare_restore_backup; valid for 60 seconds"
+    )]
+    pub confirmation_token: String,
+}
+
+#[derive(Debug, serde::Deserialize, schemars::JsonSchema)]
+pub struct TestMcpServerRequest {
+    #[schemars(description = "Name of the MCP server to test")]
+    pub name: String,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct McpToolSummary {
+    pub name: String,
+    pub description: String,
 }
 
 // MCP Server with tool router
@@ -138,21 +445,82 @@ impl McpManagerServer {
         }
     }
 
-    #[tool(description = "List all configured MCP servers in Claude Desktop")]
-    async fn list_mcp_servers(&self) -> Json<Value> {
+    // Every tool with its description and the input schema #[tool_router] generated
+    // from the request structs, sorted by name
+    pub fn tool_schemas() -> Vec<Tool> {
+        let mut tools = Self::tool_router().list_all();
+        tools.sort_by(|a, b| a.name.cmp(&b.name));
+        tools
+    }
+
+    // Name and description of every registered tool, e.g. for a "what can the agent
+    // do" panel
+    pub fn tool_summaries() -> Vec<McpToolSummary> {
+        Self::tool_schemas()
+            .into_iter()
+            .map(|tool| McpToolSummary {
+                name: tool.name.to_string(),
+                description: tool.description.as_deref().unwrap_or_default().to_string(),
+            })
+            .collect()
+    }
+
+    #[tool(
+        description = "List configured MCP servers in Claude Desktop, optionally paged with offset and limit"
+    )]
+    async fn list_mcp_servers(
+        &self,
+        Parameters(ListMcpServersRequest { offset, limit }): Parameters<ListMcpServersRequest>,
+    ) -> Json<Value> {
         match crate::internal_parse_claude_json(&self.state, None).await {
             Ok(servers) => {
                 // Convert to sanitized version (without environment variables)
+                let expose_env_keys = self.expose_env_keys().await;
                 let sanitized_servers: Vec<McpServerInfoSanitized> = servers
                     .iter()
-                    .map(|server| McpServerInfoSanitized::from_server_info(server))
+                    .skip(offset)
+                    .take(limit.unwrap_or(usize::MAX))
+                    .map(|server| McpServerInfoSanitized::from_server_info(server, expose_env_keys))
                     .collect();
-                
+                let has_more = offset + sanitized_servers.len() < servers.len();
+                let size = crate::internal_get_config_size(&self.state, None)
+                    .await
+                    .ok();
+
                 Json(json!({
                     "servers": sanitized_servers,
-                    "total_count": sanitized_servers.len()
+                    "total_count": servers.len(),
+                    "offset": offset,
+                    "limit": limit,
+                    "has_more": has_more,
+                    "config_size": size
                 }))
-            },
+            }
+            Err(e) => Json(json!({
+                "error": format!("Failed to list MCP servers: {}", e)
+            })),
+        }
+    }
+
+    #[tool(description = "List configured MCP servers whose command is of the given server type")]
+    async fn get_servers_by_type(
+        &self,
+        Parameters(GetServersByTypeRequest { server_type }): Parameters<GetServersByTypeRequest>,
+    ) -> Json<Value> {
+        match crate::internal_get_installed_servers_by_type(&self.state, &server_type, None).await {
+            Ok(servers) => {
+                let expose_env_keys = self.expose_env_keys().await;
+                let sanitized_servers: Vec<McpServerInfoSanitized> = servers
+                    .iter()
+                    .map(|server| McpServerInfoSanitized::from_server_info(server, expose_env_keys))
+                    .collect();
+
+                Json(json!({
+                    "servers": sanitized_servers,
+                    "total_count": sanitized_servers.len(),
+                    "server_type": server_type
+                }))
+            }
             Err(e) => Json(json!({
                 "error": format!("Failed to list MCP servers: {}", e)
             })),
@@ -162,15 +530,55 @@ impl McpManagerServer {
     #[tool(description = "Add a new MCP server to Claude Desktop configuration")]
     async fn add_mcp_server(
         &self,
-        Parameters(AddMcpServerRequest { name, command, args, env }): Parameters<AddMcpServerRequest>,
+        Parameters(AddMcpServerRequest {
+            name,
+            command,
+            args,
+            env,
+            cwd,
+            dry_run,
+        }): Parameters<AddMcpServerRequest>,
     ) -> Json<Value> {
+        let arguments = json!({
+            "name": name,
+            "command": command,
+            "args": args,
+            "env": env.as_ref().map(redacted_env),
+            "cwd": cwd
+        });
+        if let Some(rejection) = self.command_rejection(&command).await {
+            crate::audit_log::record(&self.state, "add_mcp_server", arguments, &rejection.0).await;
+            return rejection;
+        }
         let server_data = McpServerEdit {
             command,
             args,
             env: env.unwrap_or_default(),
+            cwd,
         };
 
-        match crate::internal_add_server(&self.state, name.clone(), server_data, None).await {
+        if dry_run {
+            let result = crate::internal_dry_run_server_change(
+                &self.state,
+                name.clone(),
+                Some(server_data),
+                true,
+                None,
+            )
+            .await;
+            return dry_run_response(result, &name, "Failed to add MCP server");
+        }
+
+        let response = match crate::internal_add_server(
+            &self.state,
+            name.clone(),
+            server_data,
+            None,
+            true,
+            None,
+        )
+        .await
+        {
             Ok(result) => {
                 if result.success {
                     Json(json!({
@@ -189,54 +597,144 @@ impl McpManagerServer {
                 "success": false,
                 "error": format!("Failed to add MCP server: {}", e)
             })),
-        }
+        };
+
+        crate::audit_log::record(&self.state, "add_mcp_server", arguments, &response.0).await;
+        response
     }
 
     #[tool(description = "Update an existing MCP server configuration")]
     async fn update_mcp_server(
         &self,
-        Parameters(UpdateMcpServerRequest { name, command, args, env }): Parameters<UpdateMcpServerRequest>,
+        Parameters(UpdateMcpServerRequest {
+            name,
+            command,
+            args,
+            env,
+            cwd,
+            dry_run,
+        }): Parameters<UpdateMcpServerRequest>,
     ) -> Json<Value> {
+        let arguments = json!({
+            "name": name,
+            "command": command,
+            "args": args,
+            "env": env.as_ref().map(redacted_env),
+            "cwd": cwd
+        });
+        if let Some(rejection) = self.command_rejection(&command).await {
+            crate::audit_log::record(&self.state, "update_mcp_server", arguments, &rejection.0)
+                .await;
+            return rejection;
+        }
         let server_data = McpServerEdit {
             command,
             args,
             env: env.unwrap_or_default(),
+            cwd,
         };
 
-        match crate::update_server(name.clone(), server_data, None) {
-            Ok(result) => {
-                if result.success {
-                    Json(json!({
-                        "success": true,
-                        "message": result.message,
-                        "server_name": name
-                    }))
-                } else {
-                    Json(json!({
-                        "success": false,
-                        "error": result.message
-                    }))
-                }
-            }
-            Err(e) => Json(json!({
-                "success": false,
-                "error": format!("Failed to update MCP server: {}", e)
-            })),
+        if dry_run {
+            let result = crate::internal_dry_run_server_change(
+                &self.state,
+                name.clone(),
+                Some(server_data),
+                false,
+                None,
+            )
+            .await;
+            return dry_run_response(result, &name, "Failed to update MCP server");
         }
+
+        let response =
+            match crate::internal_update_server(&self.state, name.clone(), server_data, None, None)
+                .await
+            {
+                Ok(result) => {
+                    if result.success {
+                        Json(json!({
+                            "success": true,
+                            "message": result.message,
+                            "server_name": name
+                        }))
+                    } else {
+                        Json(json!({
+                            "success": false,
+                            "error": result.message
+                        }))
+                    }
+                }
+                Err(e) => Json(json!({
+                    "success": false,
+                    "error": format!("Failed to update MCP server: {}", e)
+                })),
+            };
+
+        crate::audit_log::record(&self.state, "update_mcp_server", arguments, &response.0).await;
+        response
     }
 
     #[tool(description = "Delete an MCP server from Claude Desktop configuration")]
     async fn delete_mcp_server(
         &self,
-        Parameters(DeleteMcpServerRequest { name }): Parameters<DeleteMcpServerRequest>,
+        Parameters(DeleteMcpServerRequest { name, dry_run }): Parameters<DeleteMcpServerRequest>,
+    ) -> Json<Value> {
+        if dry_run {
+            let result =
+                crate::internal_dry_run_server_change(&self.state, name.clone(), None, false, None)
+                    .await;
+            return dry_run_response(result, &name, "Failed to delete MCP server");
+        }
+
+        let arguments = json!({ "name": name });
+        let response =
+            match crate::internal_delete_server(&self.state, name.clone(), None, None).await {
+                Ok(result) => {
+                    if result.success {
+                        Json(json!({
+                            "success": true,
+                            "message": result.message,
+                            "server_name": name
+                        }))
+                    } else {
+                        Json(json!({
+                            "success": false,
+                            "error": result.message
+                        }))
+                    }
+                }
+                Err(e) => Json(json!({
+                    "success": false,
+                    "error": format!("Failed to delete MCP server: {}", e)
+                })),
+            };
+
+        crate::audit_log::record(&self.state, "delete_mcp_server", arguments, &response.0).await;
+        response
+    }
+
+    #[tool(description = "Rename an MCP server, keeping its configuration")]
+    async fn rename_mcp_server(
+        &self,
+        Parameters(RenameMcpServerRequest { old_name, new_name }): Parameters<
+            RenameMcpServerRequest,
+        >,
     ) -> Json<Value> {
-        match crate::internal_delete_server(&self.state, name.clone(), None).await {
+        let arguments = json!({ "old_name": old_name, "new_name": new_name });
+        let response = match crate::internal_rename_server(
+            &self.state,
+            old_name,
+            new_name.clone(),
+            None,
+        )
+        .await
+        {
             Ok(result) => {
                 if result.success {
                     Json(json!({
                         "success": true,
                         "message": result.message,
-                        "server_name": name
+                        "server_name": new_name.trim()
                     }))
                 } else {
                     Json(json!({
@@ -247,9 +745,60 @@ impl McpManagerServer {
             }
             Err(e) => Json(json!({
                 "success": false,
-                "error": format!("Failed to delete MCP server: {}", e)
+                "error": format!("Failed to rename MCP server: {}", e)
             })),
-        }
+        };
+
+        crate::audit_log::record(&self.state, "rename_mcp_server", arguments, &response.0).await;
+        response
+    }
+
+    #[tool(
+        description = "Append a single argument to an MCP server's args and return the resulting args"
+    )]
+    async fn append_server_arg(
+        &self,
+        Parameters(AppendServerArgRequest { name, value }): Parameters<AppendServerArgRequest>,
+    ) -> Json<Value> {
+        let arguments = json!({ "name": name, "value": value });
+        let response = self.edit_server_arg(name, ArgEdit::Append(value)).await;
+
+        crate::audit_log::record(&self.state, "append_server_arg", arguments, &response.0).await;
+        response
+    }
+
+    #[tool(
+        description = "Remove a single argument from an MCP server's args, by index or by value, and return the resulting args"
+    )]
+    async fn remove_server_arg(
+        &self,
+        Parameters(RemoveServerArgRequest { name, index, value }): Parameters<
+            RemoveServerArgRequest,
+        >,
+    ) -> Json<Value> {
+        let arguments = json!({ "name": name, "index": index, "value": value });
+        let response = self
+            .edit_server_arg(name, ArgEdit::Remove { index, value })
+            .await;
+
+        crate::audit_log::record(&self.state, "remove_server_arg", arguments, &response.0).await;
+        response
+    }
+
+    #[tool(
+        description = "Replace the argument at an index in an MCP server's args and return the resulting args"
+    )]
+    async fn set_server_arg(
+        &self,
+        Parameters(SetServerArgRequest { name, index, value }): Parameters<SetServerArgRequest>,
+    ) -> Json<Value> {
+        let arguments = json!({ "name": name, "index": index, "value": value });
+        let response = self
+            .edit_server_arg(name, ArgEdit::Set { index, value })
+            .await;
+
+        crate::audit_log::record(&self.state, "set_server_arg", arguments, &response.0).await;
+        response
     }
 
     #[tool(description = "Get detailed information about a specific MCP server")]
@@ -257,54 +806,213 @@ impl McpManagerServer {
         &self,
         Parameters(GetMcpServerDetailsRequest { name }): Parameters<GetMcpServerDetailsRequest>,
     ) -> Json<Value> {
-        let config_path = {
-            let path_guard = self.state.config_path.read().await;
-            if path_guard.is_empty() {
-                None
-            } else {
-                Some(path_guard.clone())
-            }
-        };
-
-        match crate::get_server_details(name.clone(), config_path) {
+        match crate::get_server_details(name.clone(), None) {
             Ok(server_info) => {
                 // Convert to sanitized version (without environment variables)
-                let sanitized_server = McpServerInfoSanitized::from_server_info(&server_info);
-                
+                let sanitized_server = McpServerInfoSanitized::from_server_info(
+                    &server_info,
+                    self.expose_env_keys().await,
+                );
+
                 Json(json!({
                     "server": sanitized_server,
                     "name": name
                 }))
-            },
+            }
             Err(e) => Json(json!({
                 "error": format!("Failed to get server details: {}", e)
             })),
         }
     }
 
+    #[tool(
+        description = "Launch a configured MCP server briefly and check that it responds to the MCP initialize handshake"
+    )]
+    async fn test_mcp_server(
+        &self,
+        Parameters(TestMcpServerRequest { name }): Parameters<TestMcpServerRequest>,
+    ) -> Json<Value> {
+        match crate::internal_test_server(&self.state, name).await {
+            Ok(health) => Json(json!(health)),
+            Err(e) => Json(json!({
+                "error": format!("Failed to test MCP server: {}", e)
+            })),
+        }
+    }
+
+    #[tool(description = "Create a manual backup of the Claude Desktop configuration")]
+    async fn create_backup(&self) -> Json<Value> {
+        let response = match crate::create_manual_backup(None) {
+            Ok(result) if result.success => Json(json!({
+                "success": true,
+                "message": result.message
+            })),
+            Ok(result) => Json(json!({
+                "success": false,
+                "error": result.message
+            })),
+            Err(e) => Json(json!({
+                "success": false,
+                "error": format!("Failed to create backup: {}", e)
+            })),
+        };
+
+        crate::audit_log::record(&self.state, "create_backup", json!({}), &response.0).await;
+        response
+    }
+
+    #[tool(
+        description = "List configuration backups, newest first, with their paths and whether they are valid"
+    )]
+    async fn list_backups(&self) -> Json<Value> {
+        match crate::list_backups(None) {
+            Ok(backups) => Json(json!({
+                "backups": backups,
+                "total_count": backups.len()
+            })),
+            Err(e) => Json(json!({
+                "error": format!("Failed to list backups: {}", e)
+            })),
+        }
+    }
+
+    #[tool(
+        description = "Preview restoring a backup and get the confirmation token that restore_backup requires"
+    )]
+    async fn prepare_restore_backup(
+        &self,
+        Parameters(PrepareRestoreRequest { backup_path }): Parameters<PrepareRestoreRequest>,
+    ) -> Json<Value> {
+        match crate::internal_prepare_restore(&self.state, None, backup_path).await {
+            Ok(preview) => Json(json!(preview)),
+            Err(e) => Json(json!({
+                "error": format!("Failed to prepare restore: {}", e)
+            })),
+        }
+    }
+
+    #[tool(
+        description = "Restore a backup previewed with prepare_restore_backup, overwriting the current configuration"
+    )]
+    async fn restore_backup(
+        &self,
+        Parameters(RestoreBackupRequest { confirmation_token }): Parameters<RestoreBackupRequest>,
+    ) -> Json<Value> {
+        let response = match crate::internal_restore_from_backup(
+            &self.state,
+            confirmation_token,
+            None,
+        )
+        .await
+        {
+            Ok(result) if result.success => Json(json!({
+                "success": true,
+                "message": result.message
+            })),
+            Ok(result) => Json(json!({
+                "success": false,
+                "error": result.message
+            })),
+            Err(e) => Json(json!({
+                "success": false,
+                "error": format!("Failed to restore backup: {}", e)
+            })),
+        };
+
+        // The token is deliberately left out of the log
+        crate::audit_log::record(&self.state, "restore_backup", json!({}), &response.0).await;
+        response
+    }
+
+    #[tool(
+        description = "Liveness check: confirms the transport and tool router respond, without touching the config"
+    )]
+    async fn ping(&self) -> Json<Value> {
+        Json(json!({
+            "pong": true,
+            "server_time": crate::unix_now(),
+            "version": env!("CARGO_PKG_VERSION")
+        }))
+    }
+
+    #[tool(
+        description = "Get the status of this MCP Manager server: port, URL, PID, uptime and the last error"
+    )]
+    async fn get_server_status(&self) -> Json<Value> {
+        Json(json!(
+            crate::internal_get_mcp_server_status(&self.state).await
+        ))
+    }
+
+    #[tool(
+        description = "Schedule this MCP Manager server to stop after a short delay. The current connection is closed when it stops"
+    )]
+    async fn stop_server(
+        &self,
+        Parameters(StopServerRequest { delay_seconds }): Parameters<StopServerRequest>,
+    ) -> Json<Value> {
+        let arguments = json!({ "delay_seconds": delay_seconds });
+        let delay_seconds = delay_seconds.unwrap_or(DEFAULT_STOP_DELAY_SECS);
+
+        let response = if !(1..=MAX_STOP_DELAY_SECS).contains(&delay_seconds) {
+            Json(json!({
+                "success": false,
+                "error": format!("delay_seconds must be between 1 and {}", MAX_STOP_DELAY_SECS)
+            }))
+        } else {
+            crate::schedule_mcp_server_stop(
+                &self.state,
+                std::time::Duration::from_secs(delay_seconds),
+            );
+            Json(json!({
+                "success": true,
+                "message": format!("MCP server will stop in {} seconds", delay_seconds)
+            }))
+        };
+
+        crate::audit_log::record(&self.state, "stop_server", arguments, &response.0).await;
+        response
+    }
+
+    #[tool(
+        description = "Get MCP Manager's settings, such as the Claude Desktop config path and MCP server port, with secrets omitted"
+    )]
+    async fn get_app_settings(&self) -> Json<Value> {
+        let settings = self.state.settings_cache.read().await.clone();
+
+        Json(json!({
+            "settings": AppSettingsSanitized::from_settings(&settings)
+        }))
+    }
+
     #[tool(description = "Get a list of all available preset MCP servers that can be installed")]
     async fn get_preset_servers(&self) -> Json<Value> {
         let presets = crate::get_preset_servers();
-        
+
         // Convert to sanitized version (without environment variables)
+        let expose_env_keys = self.expose_env_keys().await;
         let sanitized_presets: Vec<PresetServerSanitized> = presets
             .iter()
-            .map(|preset| PresetServerSanitized::from_preset_server(preset))
+            .map(|preset| PresetServerSanitized::from_preset_server(preset, expose_env_keys))
             .collect();
-        
+
         Json(json!({
             "preset_servers": sanitized_presets,
             "total_count": sanitized_presets.len()
         }))
     }
 
-    #[tool(description = "Get available preset MCP servers with option to exclude already installed ones")]
+    #[tool(
+        description = "Get available preset MCP servers with option to exclude already installed ones"
+    )]
     async fn get_preset_servers_filtered(
         &self,
-        Parameters(GetPresetServersRequest { exclude_installed }): Parameters<GetPresetServersRequest>,
+        Parameters(GetPresetServersRequest { exclude_installed }): Parameters<
+            GetPresetServersRequest,
+        >,
     ) -> Json<Value> {
         let presets = crate::get_preset_servers();
-        
+
         let filtered_presets = if exclude_installed {
             // Get currently installed servers to filter out
             match crate::internal_parse_claude_json(&self.state, None).await {
@@ -313,7 +1021,7 @@ impl McpManagerServer {
                         .iter()
                         .map(|server| server.name.clone())
                         .collect();
-                    
+
                     presets
                         .into_iter()
                         .filter(|preset| !installed_names.contains(&preset.name))
@@ -327,13 +1035,14 @@ impl McpManagerServer {
         } else {
             presets
         };
-        
+
         // Convert to sanitized version (without environment variables)
+        let expose_env_keys = self.expose_env_keys().await;
         let sanitized_presets: Vec<PresetServerSanitized> = filtered_presets
             .iter()
-            .map(|preset| PresetServerSanitized::from_preset_server(preset))
+            .map(|preset| PresetServerSanitized::from_preset_server(preset, expose_env_keys))
             .collect();
-        
+
         Json(json!({
             "preset_servers": sanitized_presets,
             "total_count": sanitized_presets.len(),
@@ -342,43 +1051,233 @@ impl McpManagerServer {
         }))
     }
 
-    #[tool(description = "Install a preset MCP server with optional API keys")]
-    async fn install_preset_server(
+    #[tool(description = "List the categories used to group preset MCP servers")]
+    async fn list_preset_categories(&self) -> Json<Value> {
+        let categories = crate::get_preset_server_categories();
+
+        Json(json!({
+            "categories": categories,
+            "total_count": categories.len()
+        }))
+    }
+
+    #[tool(
+        description = "List the server types (docker, npx, uvx, ...) used by preset MCP servers"
+    )]
+    async fn list_preset_server_types(&self) -> Json<Value> {
+        let server_types = crate::get_server_types();
+
+        Json(json!({
+            "server_types": server_types,
+            "total_count": server_types.len()
+        }))
+    }
+
+    #[tool(description = "Get the preset MCP servers in a specific category")]
+    async fn get_presets_by_category(
         &self,
-        Parameters(InstallPresetServerRequest { preset_name, api_keys }): Parameters<InstallPresetServerRequest>,
+        Parameters(GetPresetsByCategoryRequest { category }): Parameters<
+            GetPresetsByCategoryRequest,
+        >,
     ) -> Json<Value> {
-        // Get preset server details
-        let preset = match crate::get_preset_server_by_name(preset_name.clone()) {
-            Some(preset) => preset,
-            None => {
-                return Json(json!({
-                    "success": false,
-                    "error": format!("Preset server '{}' not found", preset_name)
-                }))
+        let expose_env_keys = self.expose_env_keys().await;
+        let sanitized_presets: Vec<PresetServerSanitized> =
+            crate::get_preset_servers_by_category(category.clone())
+                .iter()
+                .map(|preset| PresetServerSanitized::from_preset_server(preset, expose_env_keys))
+                .collect();
+
+        Json(json!({
+            "preset_servers": sanitized_presets,
+            "total_count": sanitized_presets.len(),
+            "category": category
+        }))
+    }
+
+    #[tool(description = "Get the preset MCP servers that do or do not require API keys")]
+    async fn get_presets_by_api_requirement(
+        &self,
+        Parameters(GetPresetsByApiRequirementRequest { requires_api_key }): Parameters<
+            GetPresetsByApiRequirementRequest,
+        >,
+    ) -> Json<Value> {
+        let expose_env_keys = self.expose_env_keys().await;
+        let sanitized_presets: Vec<PresetServerSanitized> =
+            crate::get_preset_servers_by_api_requirement(requires_api_key)
+                .iter()
+                .map(|preset| PresetServerSanitized::from_preset_server(preset, expose_env_keys))
+                .collect();
+
+        Json(json!({
+            "preset_servers": sanitized_presets,
+            "total_count": sanitized_presets.len(),
+            "requires_api_key": requires_api_key
+        }))
+    }
+
+    #[tool(
+        description = "Fuzzy search preset MCP servers by name, description or category, ranked by relevance"
+    )]
+    async fn search_preset_servers(
+        &self,
+        Parameters(SearchPresetServersRequest {
+            query,
+            include_installed,
+        }): Parameters<SearchPresetServersRequest>,
+    ) -> Json<Value> {
+        let installed_names: Vec<String> = if include_installed {
+            match crate::internal_parse_claude_json(&self.state, None).await {
+                Ok(servers) => servers.into_iter().map(|server| server.name).collect(),
+                Err(e) => {
+                    return Json(json!({
+                        "error": format!("Failed to load installed servers: {}", e)
+                    }))
+                }
             }
+        } else {
+            Vec::new()
         };
 
-        // Prepare server data with API keys if provided
-        let mut env = preset.env.unwrap_or_default();
-        if let Some(keys) = api_keys {
-            env.extend(keys);
+        let results = crate::rank_search_results(&query, &installed_names);
+
+        Json(json!({
+            "results": results,
+            "total_count": results.len(),
+            "query": query
+        }))
+    }
+
+    #[tool(
+        description = "Compare an installed MCP server with the preset of the same name and list the fields that differ"
+    )]
+    async fn compare_with_preset(
+        &self,
+        Parameters(CompareWithPresetRequest { name }): Parameters<CompareWithPresetRequest>,
+    ) -> Json<Value> {
+        match crate::internal_compare_with_preset(&self.state, name, None).await {
+            Ok(comparison) => Json(json!(comparison)),
+            Err(e) => Json(json!({
+                "error": format!("Failed to compare with preset: {}", e)
+            })),
         }
+    }
 
-        let server_data = McpServerEdit {
-            command: preset.command,
-            args: preset.args,
-            env,
-        };
+    #[tool(
+        description = "List every preset with whether it is installed and whether the installed command and args still match the preset"
+    )]
+    async fn get_preset_install_status(&self) -> Json<Value> {
+        match crate::internal_get_preset_install_status(&self.state).await {
+            Ok(statuses) => Json(json!({
+                "presets": statuses,
+                "total_count": statuses.len()
+            })),
+            Err(e) => Json(json!({
+                "error": format!("Failed to get preset install status: {}", e)
+            })),
+        }
+    }
+
+    #[tool(description = "Install a preset MCP server with optional API keys")]
+    async fn install_preset_server(
+        &self,
+        Parameters(InstallPresetServerRequest {
+            preset_name,
+            api_keys,
+            placeholders,
+            pull_image,
+            on_conflict,
+        }): Parameters<InstallPresetServerRequest>,
+    ) -> Json<Value> {
+        let arguments = json!({
+            "preset_name": preset_name,
+            "api_keys": api_keys.as_ref().map(redacted_env),
+            "placeholders": placeholders,
+            "pull_image": pull_image,
+            "on_conflict": on_conflict
+        });
+        let response = self
+            .install_preset(preset_name, api_keys, placeholders, pull_image, on_conflict)
+            .await;
 
-        match crate::internal_add_server(&self.state, preset.name.clone(), server_data, None).await
+        crate::audit_log::record(&self.state, "install_preset_server", arguments, &response.0)
+            .await;
+        response
+    }
+}
+
+impl McpManagerServer {
+    async fn expose_env_keys(&self) -> bool {
+        self.state.settings_cache.read().await.mcp_expose_env_keys
+    }
+
+    // The error response for a command outside the MCP allowlist. GUI calls skip this.
+    async fn command_rejection(&self, command: &str) -> Option<Json<Value>> {
+        let message = self
+            .state
+            .settings_cache
+            .read()
+            .await
+            .check_mcp_command(command)
+            .err()?;
+        Some(Json(json!({
+            "success": false,
+            "error": message
+        })))
+    }
+
+    async fn edit_server_arg(&self, name: String, edit: ArgEdit) -> Json<Value> {
+        match crate::internal_edit_server_arg(&self.state, name.clone(), edit, None).await {
+            Ok(result) if result.success => Json(json!({
+                "success": true,
+                "message": result.message,
+                "server_name": name,
+                "args": result.args
+            })),
+            Ok(result) => Json(json!({
+                "success": false,
+                "error": result.message,
+                "args": result.args
+            })),
+            Err(e) => Json(json!({
+                "success": false,
+                "error": format!("Failed to edit server args: {}", e)
+            })),
+        }
+    }
+
+    async fn install_preset(
+        &self,
+        preset_name: String,
+        api_keys: Option<HashMap<String, String>>,
+        placeholders: HashMap<String, String>,
+        pull_image: bool,
+        on_conflict: OnConflict,
+    ) -> Json<Value> {
+        if let Some(preset) = crate::get_preset_server_by_name(preset_name.clone()) {
+            if let Some(rejection) = self.command_rejection(&preset.command).await {
+                return rejection;
+            }
+        }
+
+        match crate::internal_install_preset(
+            &self.state,
+            preset_name.clone(),
+            api_keys.unwrap_or_default(),
+            placeholders,
+            pull_image,
+            on_conflict,
+            None,
+        )
+        .await
         {
             Ok(result) => {
                 if result.success {
                     Json(json!({
                         "success": true,
                         "message": result.message,
-                        "server_name": preset.name,
-                        "preset_name": preset_name
+                        "server_name": preset_name,
+                        "preset_name": preset_name,
+                        "image_pulled": pull_image
                     }))
                 } else {
                     Json(json!({
@@ -399,97 +1298,166 @@ impl McpManagerServer {
 #[tool_handler]
 impl ServerHandler for McpManagerServer {
     fn get_info(&self) -> ServerInfo {
+        let mut tool_names: Vec<String> = self
+            .tool_router
+            .list_all()
+            .into_iter()
+            .map(|tool| tool.name.to_string())
+            .collect();
+        tool_names.sort();
+
         ServerInfo {
-            instructions: Some("MCP Manager Server for managing Claude Desktop MCP servers. Use the available tools to list, add, update, delete, and manage MCP server configurations.".to_string()),
+            instructions: Some(format!(
+                "MCP Manager Server for managing Claude Desktop MCP servers. Use the available tools to list, add, update, delete, and manage MCP server configurations. Available tools: {}",
+                tool_names.join(", ")
+            )),
             capabilities: ServerCapabilities::builder().enable_tools().build(),
             ..Default::default()
         }
     }
 }
 
+// Reject requests that don't carry the configured `Authorization: Bearer <token>` header
+async fn require_bearer_token(
+    axum::extract::State(expected): axum::extract::State<Arc<String>>,
+    request: axum::extract::Request,
+    next: axum::middleware::Next,
+) -> Result<axum::response::Response, axum::http::StatusCode> {
+    let authorized = request
+        .headers()
+        .get(axum::http::header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(|token| constant_time_eq(token.trim().as_bytes(), expected.as_bytes()))
+        .unwrap_or(false);
+
+    if authorized {
+        Ok(next.run(request).await)
+    } else {
+        Err(axum::http::StatusCode::UNAUTHORIZED)
+    }
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
 // Start MCP server with SSE transport
 pub async fn start_mcp_server(
     state: AppState,
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    // Initialize tracing
-    tracing_subscriber::registry()
-        .with(
-            tracing_subscriber::EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| "debug".to_string().into()),
-        )
-        .with(tracing_subscriber::fmt::layer())
-        .init();
-    
     // Get settings to determine port and path
     let settings = {
         let settings_guard = state.settings_cache.read().await;
         settings_guard.clone()
     };
-    
+
     if !settings.mcp_server_enabled {
-        println!("ℹ️ MCP server is disabled in settings");
+        tracing::info!("MCP server is disabled in settings");
         return Ok(());
     }
-    
-    let bind_address: SocketAddr = format!("127.0.0.1:{}", settings.mcp_server_port).parse()?;
-    
-    println!("🔗 Starting MCP Manager Server...");
-    println!("📋 Available MCP Tools:");
-    println!("  • list_mcp_servers - List all configured MCP servers");
-    println!("  • add_mcp_server - Add a new MCP server");
-    println!("  • update_mcp_server - Update an existing MCP server");
-    println!("  • delete_mcp_server - Delete an MCP server");
-    println!("  • get_mcp_server_details - Get details of a specific server");
-    println!("  • get_preset_servers - Get available preset servers");
-    println!("  • get_preset_servers_filtered - Get preset servers with filtering options");
-    println!("  • install_preset_server - Install a preset server");
-    
+
+    tracing::info!("Starting MCP Manager Server");
+    tracing::debug!("Available MCP tools:");
+    for tool in McpManagerServer::tool_summaries() {
+        tracing::debug!("  {} - {}", tool.name, tool.description);
+    }
+
     // Get cancellation token from AppState
     let cancellation_token = {
         let token_guard = state.mcp_server_cancellation.read().await;
-        token_guard.clone().unwrap_or_else(|| CancellationToken::new())
+        token_guard
+            .clone()
+            .unwrap_or_else(|| CancellationToken::new())
     };
 
+    if settings.mcp_transport == McpTransport::Stdio {
+        return serve_stdio(state, cancellation_token).await;
+    }
+
+    let bind_ip: IpAddr = settings.mcp_bind_address.trim().parse()?;
+    let bind_address = SocketAddr::new(bind_ip, settings.mcp_server_port);
+
     // Create SSE server configuration
     let config = SseServerConfig {
         bind: bind_address,
         sse_path: settings.mcp_sse_path.clone(),
-        post_path: "/message".to_string(), // Required by SseServerConfig but not used for MCP
+        post_path: settings.mcp_post_path.clone(),
         ct: cancellation_token.clone(),
-        sse_keep_alive: None,
+        sse_keep_alive: settings
+            .mcp_sse_keep_alive_secs
+            .map(std::time::Duration::from_secs),
     };
-    
+
     let (sse_server, router) = SseServer::new(config);
-    
+
+    // Gate both the SSE and message endpoints behind the auth token when one is set
+    let router = match settings.auth_token() {
+        Some(token) => router.layer(axum::middleware::from_fn_with_state(
+            Arc::new(token.to_string()),
+            require_bearer_token,
+        )),
+        None => router,
+    };
+
     // Create TCP listener
     let listener = tokio::net::TcpListener::bind(sse_server.config.bind).await?;
-    
+
     let ct = sse_server.config.ct.child_token();
-    
+
     // Start the axum server with graceful shutdown
-    let _server_task = tokio::spawn(async move {
+    let server_task = tokio::spawn(async move {
         if let Err(e) = axum::serve(listener, router.into_make_service())
             .with_graceful_shutdown(async move {
                 ct.cancelled().await;
                 tracing::info!("SSE server cancelled");
             })
-            .await 
+            .await
         {
             tracing::error!(error = %e, "SSE server shutdown with error");
         }
     });
-    
-    println!("✅ MCP Manager Server initialized with tool support");
-    println!("🔄 Real-time GUI synchronization active");
-    println!("📡 Listening on SSE at http://{}{}...", bind_address, settings.mcp_sse_path);
-    
+
+    tracing::info!(
+        "MCP Manager Server listening on SSE at http://{}{}",
+        bind_address,
+        settings.mcp_sse_path
+    );
+
     // Start the MCP server with the service
     let mcp_server = McpManagerServer::new(state);
     let _ct = sse_server.with_service(move || mcp_server.clone());
-    
+
     // Wait for cancellation instead of ctrl_c since this is controlled by GUI
     cancellation_token.cancelled().await;
-    
-    println!("🔚 MCP server stopped");
+
+    // Let axum finish its graceful shutdown so the listener is released before we return
+    if let Err(e) = server_task.await {
+        tracing::error!(error = %e, "SSE server task failed");
+    }
+
+    tracing::info!("MCP server stopped");
+    Ok(())
+}
+
+// Serve the same tools on stdin/stdout. Runs until the GUI stops the server or the
+// client closes stdin.
+async fn serve_stdio(
+    state: AppState,
+    cancellation_token: CancellationToken,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    tracing::info!("MCP Manager Server serving on stdio");
+
+    let service = McpManagerServer::new(state.clone())
+        .serve_with_ct(rmcp::transport::stdio(), cancellation_token.clone())
+        .await?;
+    service.waiting().await?;
+
+    // The client went away on its own, so nothing else will reset the status
+    if !cancellation_token.is_cancelled() {
+        *state.mcp_server_status.write().await = crate::McpServerStatus::stopped();
+    }
+
+    tracing::info!("MCP server stopped");
     Ok(())
 }
@@ -6,17 +6,53 @@ interface McpServerInfo {
   command: string;
   args: string[];
   env: { [key: string]: string };
+  cwd?: string;
+  enabled: boolean;
 }
 
 interface McpServerEdit {
   command: string;
   args: string[];
   env: { [key: string]: string };
+  cwd?: string;
 }
 
 interface SaveResult {
   success: boolean;
   message: string;
+  path?: string;
+}
+
+// Structured error returned by the core commands (add/update/delete server, load settings)
+interface AppError {
+  kind: "io" | "parse" | "validation" | "not_found" | "port_in_use" | "other";
+  message: string;
+  path?: string;
+  port?: number;
+  details?: unknown;
+}
+
+function errorMessage(error: unknown): string {
+  if (error && typeof error === 'object' && 'message' in error) {
+    return (error as AppError).message;
+  }
+  return String(error);
+}
+
+interface InstallProgress {
+  name: string;
+  stage: 'validating' | 'pulling' | 'writing' | 'done';
+  image?: string;
+  success?: boolean;
+}
+
+interface RestorePreview {
+  token: string;
+  expires_in_seconds: number;
+  backup_path: string;
+  added: string[];
+  removed: string[];
+  changed: string[];
 }
 
 interface JsonErrorInfo {
@@ -26,6 +62,13 @@ interface JsonErrorInfo {
   column?: number;
   suggestion?: string;
   has_backup: boolean;
+  context_lines?: string[];
+}
+
+interface ConfigIoError {
+  kind: 'NotFound' | 'PermissionDenied' | 'Other';
+  path: string;
+  message: string;
 }
 
 interface BackupInfo {
@@ -41,6 +84,12 @@ interface ApiKeyRequirement {
   required: boolean;
 }
 
+interface ArgPlaceholder {
+  token: string;
+  description: string;
+  default?: string;
+}
+
 interface PresetServer {
   name: string;
   description: string;
@@ -54,6 +103,7 @@ interface PresetServer {
   // Legacy fields for backward compatibility
   apiKeyName?: string;
   apiKeyDescription?: string;
+  argPlaceholders?: ArgPlaceholder[];
 }
 
 interface McpServerStatus {
@@ -61,6 +111,7 @@ interface McpServerStatus {
   port?: number;
   ssePath?: string;
   url?: string;
+  last_error?: string;
 }
 
 let mcpListEl: HTMLElement | null;
@@ -140,6 +191,13 @@ async function showJsonErrorModal(errorInfo: JsonErrorInfo) {
   const locationInfo = errorInfo.line && errorInfo.column ? 
     `<p><strong>Location:</strong> Line ${errorInfo.line}, Column ${errorInfo.column}</p>` : '';
   
+  const contextInfo = errorInfo.context_lines && errorInfo.context_lines.length > 0 ?
+    `<pre class="error-context">${errorInfo.context_lines
+      .join('\n')
+      .replace(/&/g, '&amp;')
+      .replace(/</g, '&lt;')
+      .replace(/>/g, '&gt;')}</pre>` : '';
+  
   const suggestionInfo = errorInfo.suggestion ? 
     `<div class="error-suggestion">
       <strong>💡 Suggestion:</strong>
@@ -161,6 +219,7 @@ async function showJsonErrorModal(errorInfo: JsonErrorInfo) {
         <div class="error-details">
           <p><strong>Problem:</strong> ${errorInfo.message}</p>
           ${locationInfo}
+          ${contextInfo}
           
           ${suggestionInfo}
           
@@ -221,20 +280,7 @@ async function showJsonErrorModal(errorInfo: JsonErrorInfo) {
   
   const restoreBackupBtn = document.getElementById('restore-backup-btn');
   if (restoreBackupBtn) {
-    restoreBackupBtn.addEventListener('click', async () => {
-      try {
-        const result: SaveResult = await invoke("restore_from_backup");
-        if (result.success) {
-          showNotification("Success", result.message);
-          closeModal();
-          loadMcpServers(); // Reload after restoration
-        } else {
-          showNotification("Error", result.message);
-        }
-      } catch (error) {
-        showNotification("Error", `Failed to restore backup: ${error}`);
-      }
-    });
+    restoreBackupBtn.addEventListener('click', () => restoreFromBackup());
   }
   
   const createBackupBtn = document.getElementById('create-backup-btn');
@@ -307,20 +353,7 @@ function showBackupInfoModal(backupInfo: BackupInfo) {
   if (backupInfo.is_valid) {
     const restoreThisBackupBtn = document.getElementById('restore-this-backup-btn');
     if (restoreThisBackupBtn) {
-      restoreThisBackupBtn.addEventListener('click', async () => {
-        try {
-          const result: SaveResult = await invoke("restore_from_backup");
-          if (result.success) {
-            showNotification("Success", result.message);
-            closeModal();
-            loadMcpServers(); // Reload after restoration
-          } else {
-            showNotification("Error", result.message);
-          }
-        } catch (error) {
-          showNotification("Error", `Failed to restore backup: ${error}`);
-        }
-      });
+      restoreThisBackupBtn.addEventListener('click', () => restoreFromBackup());
     }
   }
 }
@@ -667,6 +700,18 @@ function handleJsonError(errorString: string) {
         mcpListEl.innerHTML = `<p class="error">Error loading MCP servers: ${errorString}</p>`;
       }
     }
+  } else if (errorString.startsWith("IO_ERROR:")) {
+    // Filesystem error (missing file, permission denied, ...)
+    let message = errorString;
+    try {
+      const ioError: ConfigIoError = JSON.parse(errorString.substring(9));
+      message = `${ioError.message} (${ioError.path})`;
+    } catch (parseError) {
+      // Fall back to the raw error string
+    }
+    if (mcpListEl) {
+      mcpListEl.innerHTML = `<p class="error">Error loading MCP servers: ${message}</p>`;
+    }
   } else {
     // Standard error handling
     if (mcpListEl) {
@@ -688,6 +733,11 @@ function displayMcpServers(servers: McpServerInfo[]) {
       <div class="server-header">
         <h3>${server.name}</h3>
         <div class="server-actions">
+          <button class="copy-btn" onclick="copyServerJson('${server.name}')" title="Copy as JSON (env values redacted)">
+            <svg width="16" height="16" viewBox="0 0 24 24" fill="currentColor">
+              <path d="M16 1H4c-1.1 0-2 .9-2 2v14h2V3h12V1zm3 4H8c-1.1 0-2 .9-2 2v14c0 1.1.9 2 2 2h11c1.1 0 2-.9 2-2V7c0-1.1-.9-2-2-2zm0 16H8V7h11v14z"/>
+            </svg>
+          </button>
           <button class="edit-btn" onclick="editServer('${server.name}')" title="Edit server">
             <svg width="16" height="16" viewBox="0 0 24 24" fill="currentColor">
               <path d="M3 17.25V21h3.75L17.81 9.94l-3.75-3.75L3 17.25zM20.71 7.04c.39-.39.39-1.02 0-1.41l-2.34-2.34c-.39-.39-1.02-.39-1.41 0l-1.83 1.83 3.75 3.75 1.83-1.83z"/>
@@ -741,6 +791,16 @@ async function editServer(name: string) {
   }
 }
 
+async function copyServerJson(name: string) {
+  try {
+    const json: string = await invoke("get_server_as_json", { name, redactEnv: true });
+    await navigator.clipboard.writeText(json);
+    showNotification("Copied", `${name} copied to the clipboard as JSON`);
+  } catch (error) {
+    showNotification("Error", `Failed to copy server: ${error}`);
+  }
+}
+
 async function deleteServer(name: string) {
   showConfirmation(
     "Delete Server",
@@ -755,7 +815,7 @@ async function deleteServer(name: string) {
           showNotification("Error", result.message);
         }
       } catch (error) {
-        showNotification("Error", `Error deleting server: ${error}`);
+        showNotification("Error", `Error deleting server: ${errorMessage(error)}`);
       }
     }
   );
@@ -925,6 +985,44 @@ async function showQuickInstallModal() {
   }
 }
 
+// The backend only restores with a confirmation token from prepare_restore, so show
+// what the restore would change and only use the token once the user confirms
+async function restoreFromBackup() {
+  try {
+    const preview: RestorePreview = await invoke("prepare_restore");
+    showConfirmation(
+      "Restore Backup",
+      describeRestorePreview(preview),
+      async () => {
+        try {
+          const result: SaveResult = await invoke("restore_from_backup", { confirmationToken: preview.token });
+          if (result.success) {
+            showNotification("Success", result.message);
+            loadMcpServers(); // Reload after restoration
+          } else {
+            showNotification("Error", result.message);
+          }
+        } catch (error) {
+          showNotification("Error", `Failed to restore backup: ${error}`);
+        }
+      }
+    );
+  } catch (error) {
+    showNotification("Error", `Failed to prepare restore: ${error}`);
+  }
+}
+
+function describeRestorePreview(preview: RestorePreview): string {
+  const list = (names: string[]) => names.length > 0 ? names.join(', ') : 'none';
+  return [
+    `Restore ${preview.backup_path} over the current configuration?`,
+    `Added: ${list(preview.added)}`,
+    `Removed: ${list(preview.removed)}`,
+    `Changed: ${list(preview.changed)}`,
+    `Confirm within ${preview.expires_in_seconds} seconds.`
+  ].join('\n');
+}
+
 async function getCurrentServerNames(): Promise<string[]> {
   try {
     const customPath = appSettings.claudeConfigPath || null;
@@ -968,14 +1066,13 @@ async function installPresetServer(serverName: string) {
 }
 
 async function performServerInstallation(server: PresetServer, env: { [key: string]: string }) {
-  const serverData: McpServerEdit = {
-    command: server.command,
-    args: server.args,
-    env: { ...server.env, ...env }
-  };
-  
   try {
-    const result: SaveResult = await invoke("add_server", { name: server.name, serverData });
+    // Placeholders fall back to their defaults on the backend
+    const result: SaveResult = await invoke("install_preset_server", {
+      presetName: server.name,
+      apiKeys: env,
+      pullImage: server.serverType === "docker"
+    });
     
     if (result.success) {
       showNotification("Success", `${server.name} installed successfully!`);
@@ -985,7 +1082,7 @@ async function performServerInstallation(server: PresetServer, env: { [key: stri
       showNotification("Installation Failed", result.message);
     }
   } catch (error) {
-    showNotification("Installation Error", `Failed to install ${server.name}: ${error}`);
+    showNotification("Installation Error", `Failed to install ${server.name}: ${errorMessage(error)}`);
   }
 }
 
@@ -1164,6 +1261,11 @@ function showEditModal(server: McpServerInfo) {
             <input type="text" id="server-args" value="${server.args.join(' ')}">
           </div>
           
+          <div class="form-group">
+            <label for="server-cwd">Working Directory (optional):</label>
+            <input type="text" id="server-cwd" value="${server.cwd ?? ''}">
+          </div>
+          
           <div class="form-group">
             <label>Environment Variables:</label>
             <div id="env-vars">
@@ -1247,6 +1349,7 @@ async function handleFormSubmit(e: Event) {
   const command = (document.querySelector("#server-command") as HTMLInputElement).value.trim();
   const argsString = (document.querySelector("#server-args") as HTMLInputElement).value.trim();
   const args = argsString ? argsString.split(' ').filter(arg => arg.length > 0) : [];
+  const cwd = (document.querySelector("#server-cwd") as HTMLInputElement).value.trim();
   
   // Collect environment variables
   const env: { [key: string]: string } = {};
@@ -1267,7 +1370,8 @@ async function handleFormSubmit(e: Event) {
   const serverData: McpServerEdit = {
     command,
     args,
-    env
+    env,
+    cwd: cwd || undefined
   };
   
   try {
@@ -1286,7 +1390,7 @@ async function handleFormSubmit(e: Event) {
       showNotification("Error", result.message);
     }
   } catch (error) {
-    showNotification("Error", `Error saving server: ${error}`);
+    showNotification("Error", `Error saving server: ${errorMessage(error)}`);
   }
 }
 
@@ -1338,11 +1442,13 @@ async function handleImportSubmit(e: Event) {
         const command = serverConfig.command;
         const args = Array.isArray(serverConfig.args) ? serverConfig.args : [];
         const env = (typeof serverConfig.env === 'object' && serverConfig.env !== null) ? serverConfig.env : {};
+        const cwd = typeof serverConfig.cwd === 'string' ? serverConfig.cwd : undefined;
         
         const serverData: McpServerEdit = {
           command,
           args,
-          env
+          env,
+          cwd
         };
         
         // Add the server
@@ -1354,7 +1460,7 @@ async function handleImportSubmit(e: Event) {
           errorMessages.push(`Server '${serverName}': ${result.message}`);
         }
       } catch (error) {
-        errorMessages.push(`Server '${serverName}': ${error}`);
+        errorMessages.push(`Server '${serverName}': ${errorMessage(error)}`);
       }
     }
     
@@ -1387,6 +1493,7 @@ async function handleImportSubmit(e: Event) {
 // Make functions globally available
 (window as any).editServer = editServer;
 (window as any).deleteServer = deleteServer;
+(window as any).copyServerJson = copyServerJson;
 (window as any).addEnvVar = addEnvVar;
 (window as any).removeEnvVar = removeEnvVar;
 (window as any).toggleEnvVisibility = toggleEnvVisibility;
@@ -1421,7 +1528,7 @@ async function updateMcpServerStatus() {
       } else {
         statusIndicator!.textContent = "● Offline";
         statusIndicator!.className = "status-offline";
-        statusUrl!.textContent = "";
+        statusUrl!.textContent = status.last_error ? `Last error: ${status.last_error}` : "";
         startBtn.style.display = "inline-block";
         stopBtn.style.display = "none";
       }
@@ -1494,6 +1601,20 @@ function setupRealtimeEventListeners() {
     loadMcpServers();
   });
   
+  // Listen for preset install progress (image pulls can take a while)
+  listen<InstallProgress>('install-progress', (event) => {
+    console.log('Install progress:', event.payload);
+    if (event.payload.stage === 'pulling') {
+      showNotification("Installing", `Pulling ${event.payload.image} for ${event.payload.name}...`);
+    }
+  });
+  
+  // Keep the theme in sync when another window toggles it
+  listen<{ darkMode: boolean }>('theme-changed', (event) => {
+    appSettings.darkMode = event.payload.darkMode;
+    applyDarkMode(event.payload.darkMode);
+  });
+
   // Listen for settings changes
   listen('settings-changed', (event) => {
     console.log('Settings changed via MCP server:', event.payload);